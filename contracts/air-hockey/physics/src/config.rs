@@ -0,0 +1,129 @@
+//! Physics configuration
+//!
+//! Mirrors `PHYSICS_CONFIG` in `server/src/physics/config.ts`. Keep the two
+//! in sync: a replay only reproduces a match if both sides use the same
+//! parameters.
+//!
+//! The mirror covers every server parameter, but the simulation only reads
+//! those that affect a replay. Masses, surface friction, the puck's minimum
+//! speed, the wall thickness and the countdown are kept for parity and are
+//! not used by `Simulation`.
+
+use crate::fixed::Fixed;
+
+/// Table dimensions, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableConfig {
+    pub width: Fixed,
+    pub height: Fixed,
+    /// Not used by the simulation
+    pub wall_thickness: Fixed,
+    pub goal_width: Fixed,
+}
+
+/// Puck parameters; speeds are in pixels per tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PuckConfig {
+    pub radius: Fixed,
+    /// Not used by the simulation
+    pub mass: Fixed,
+    pub restitution: Fixed,
+    /// Not used by the simulation
+    pub friction: Fixed,
+    pub friction_air: Fixed,
+    pub max_speed: Fixed,
+    /// Not used by the simulation
+    pub min_speed: Fixed,
+}
+
+/// Paddle parameters; velocities are in pixels per tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddleConfig {
+    pub radius: Fixed,
+    /// Not used by the simulation; paddles are position-controlled
+    pub mass: Fixed,
+    pub restitution: Fixed,
+    /// Not used by the simulation
+    pub friction: Fixed,
+    pub velocity_transfer: Fixed,
+    pub max_velocity: Fixed,
+}
+
+/// Wall parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallConfig {
+    pub restitution: Fixed,
+    /// Not used by the simulation
+    pub friction: Fixed,
+}
+
+/// Match flow parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameConfig {
+    pub max_score: u8,
+    /// Not used by the simulation; replays start at the first tick of play
+    pub countdown_seconds: u32,
+    pub goal_pause_ms: u32,
+    /// Physics updates per second
+    pub tick_rate: u32,
+}
+
+impl GameConfig {
+    /// Number of ticks the puck stays off the table after a goal
+    pub const fn goal_pause_ticks(&self) -> u32 {
+        self.goal_pause_ms * self.tick_rate / 1000
+    }
+}
+
+/// Full physics configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicsConfig {
+    pub table: TableConfig,
+    pub puck: PuckConfig,
+    pub paddle: PaddleConfig,
+    pub wall: WallConfig,
+    pub game: GameConfig,
+}
+
+/// Parameters used by the game server
+pub const PHYSICS_CONFIG: PhysicsConfig = PhysicsConfig {
+    table: TableConfig {
+        width: Fixed::from_int(500),
+        height: Fixed::from_int(750),
+        wall_thickness: Fixed::from_int(20),
+        goal_width: Fixed::from_int(188),
+    },
+    puck: PuckConfig {
+        radius: Fixed::from_int(12),
+        mass: Fixed::from_ratio(1, 10),
+        restitution: Fixed::from_ratio(98, 100),
+        friction: Fixed::ZERO,
+        friction_air: Fixed::from_ratio(5, 10_000),
+        max_speed: Fixed::from_int(25),
+        min_speed: Fixed::from_int(2),
+    },
+    paddle: PaddleConfig {
+        radius: Fixed::from_int(30),
+        mass: Fixed::ONE,
+        restitution: Fixed::from_ratio(8, 10),
+        friction: Fixed::from_ratio(1, 10),
+        velocity_transfer: Fixed::from_ratio(8, 10),
+        max_velocity: Fixed::from_int(30),
+    },
+    wall: WallConfig {
+        restitution: Fixed::from_ratio(9, 10),
+        friction: Fixed::ZERO,
+    },
+    game: GameConfig {
+        max_score: 7,
+        countdown_seconds: 3,
+        goal_pause_ms: 3500,
+        tick_rate: 60,
+    },
+};
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        PHYSICS_CONFIG
+    }
+}
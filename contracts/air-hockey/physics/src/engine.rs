@@ -0,0 +1,503 @@
+//! Deterministic simulation
+//!
+//! A fixed-timestep port of `server/src/physics/engine.ts`. One call to
+//! `Simulation::step` advances the match by one tick (1/`tick_rate` s), so
+//! replaying the same input log always yields the same goals and score.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::PhysicsConfig,
+    fixed::{Fixed, Vec2},
+};
+
+/// Distance below which puck and paddle centres are treated as overlapping
+const OVERLAP_EPSILON: Fixed = Fixed::from_ratio(1, 10);
+
+/// A player; player 1 defends the bottom goal (y > 0), player 2 the top one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    fn index(self) -> usize {
+        match self {
+            Player::One => 0,
+            Player::Two => 1,
+        }
+    }
+
+    fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
+
+/// A paddle target reported by a player, applied at the start of `tick`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFrame {
+    pub tick: u32,
+    pub player: Player,
+    pub target: Vec2,
+}
+
+/// A goal scored during the match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Goal {
+    pub tick: u32,
+    pub scorer: Player,
+}
+
+/// Position and velocity of a moving body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Body {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// Result of replaying an input log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    pub player1_score: u8,
+    pub player2_score: u8,
+    /// Ticks simulated before the match ended or the tick limit was hit
+    pub ticks: u32,
+    pub goals: Vec<Goal>,
+}
+
+/// Air hockey match simulation
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    config: PhysicsConfig,
+    tick: u32,
+    puck: Body,
+    paddles: [Body; 2],
+    paddle_targets: [Option<Vec2>; 2],
+    /// Whether each paddle touched the puck last tick; velocity transfer
+    /// only applies when a contact starts
+    paddle_contact: [bool; 2],
+    score: [u8; 2],
+    goals: Vec<Goal>,
+    /// Ticks left in the post-goal pause, and who receives the serve
+    goal_pause: Option<(u32, Player)>,
+}
+
+impl Simulation {
+    /// Start a match with the puck at centre and paddles in their halves
+    pub fn new(config: PhysicsConfig) -> Self {
+        let quarter = config.table.height / Fixed::from_int(4);
+        Self {
+            config,
+            tick: 0,
+            puck: Body::default(),
+            paddles: [
+                Body {
+                    position: Vec2::new(Fixed::ZERO, quarter),
+                    velocity: Vec2::ZERO,
+                },
+                Body {
+                    position: Vec2::new(Fixed::ZERO, -quarter),
+                    velocity: Vec2::ZERO,
+                },
+            ],
+            paddle_targets: [None, None],
+            paddle_contact: [false, false],
+            score: [0, 0],
+            goals: Vec::new(),
+            goal_pause: None,
+        }
+    }
+
+    /// Current tick
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Puck state
+    pub fn puck(&self) -> Body {
+        self.puck
+    }
+
+    /// Paddle state for a player
+    pub fn paddle(&self, player: Player) -> Body {
+        self.paddles[player.index()]
+    }
+
+    /// Current score as (player 1, player 2)
+    pub fn score(&self) -> (u8, u8) {
+        (self.score[0], self.score[1])
+    }
+
+    /// Goals scored so far
+    pub fn goals(&self) -> &[Goal] {
+        &self.goals
+    }
+
+    /// Check if a player has reached the winning score
+    pub fn is_finished(&self) -> bool {
+        self.score.iter().any(|&s| s >= self.config.game.max_score)
+    }
+
+    /// Set where a paddle moves on the next tick, clamped to the player's half
+    pub fn set_paddle_target(&mut self, player: Player, target: Vec2) {
+        let half_width = self.config.table.width / Fixed::from_int(2);
+        let half_height = self.config.table.height / Fixed::from_int(2);
+        let radius = self.config.paddle.radius;
+
+        let x = target.x.clamp(-half_width + radius, half_width - radius);
+        let y = match player {
+            Player::One => target.y.clamp(radius, half_height - radius),
+            Player::Two => target.y.clamp(-(half_height - radius), -radius),
+        };
+
+        self.paddle_targets[player.index()] = Some(Vec2::new(x, y));
+    }
+
+    /// Advance the simulation by one tick
+    pub fn step(&mut self) {
+        self.tick += 1;
+
+        if self.is_finished() {
+            return;
+        }
+
+        // Physics stops while the puck is off the table after a goal
+        if let Some((ticks_left, serve_toward)) = self.goal_pause {
+            if ticks_left > 1 {
+                self.goal_pause = Some((ticks_left - 1, serve_toward));
+            } else {
+                self.goal_pause = None;
+                self.reset_puck(serve_toward);
+            }
+            return;
+        }
+
+        self.update_paddles();
+        self.cap_puck_speed();
+
+        self.puck.position += self.puck.velocity;
+        self.puck.velocity = self
+            .puck
+            .velocity
+            .scale(Fixed::ONE - self.config.puck.friction_air);
+
+        self.resolve_walls();
+        for player in [Player::One, Player::Two] {
+            self.resolve_paddle(player);
+        }
+        self.cap_puck_speed();
+
+        if let Some(scorer) = self.check_goal() {
+            self.score[scorer.index()] += 1;
+            self.goals.push(Goal {
+                tick: self.tick,
+                scorer,
+            });
+            self.puck.velocity = Vec2::ZERO;
+
+            // Serve to the player who conceded
+            let pause = self.config.game.goal_pause_ticks().max(1);
+            self.goal_pause = Some((pause, scorer.other()));
+        }
+    }
+
+    /// Move paddles onto their targets; velocity is the per-tick displacement,
+    /// capped per axis
+    fn update_paddles(&mut self) {
+        let max = self.config.paddle.max_velocity;
+        for (paddle, target) in self.paddles.iter_mut().zip(self.paddle_targets) {
+            let Some(target) = target else {
+                continue;
+            };
+            let delta = target - paddle.position;
+            paddle.velocity = Vec2::new(delta.x.clamp(-max, max), delta.y.clamp(-max, max));
+            paddle.position = target;
+        }
+    }
+
+    /// Scale the puck velocity down to the maximum speed
+    fn cap_puck_speed(&mut self) {
+        let max = self.config.puck.max_speed;
+        let speed = self.puck.velocity.length();
+        if speed > max {
+            self.puck.velocity = self.puck.velocity.scale(max / speed);
+        }
+    }
+
+    /// Bounce the puck off the side walls and the goal-line walls outside the mouth
+    fn resolve_walls(&mut self) {
+        let half_width = self.config.table.width / Fixed::from_int(2);
+        let half_height = self.config.table.height / Fixed::from_int(2);
+        let half_goal = self.config.table.goal_width / Fixed::from_int(2);
+        let radius = self.config.puck.radius;
+        let restitution = self
+            .config
+            .puck
+            .restitution
+            .max(self.config.wall.restitution);
+
+        let position = &mut self.puck.position;
+        let velocity = &mut self.puck.velocity;
+
+        if position.x - radius < -half_width {
+            position.x = -half_width + radius;
+            velocity.x = -velocity.x * restitution;
+        } else if position.x + radius > half_width {
+            position.x = half_width - radius;
+            velocity.x = -velocity.x * restitution;
+        }
+
+        if position.x.abs() > half_goal {
+            if position.y - radius < -half_height {
+                position.y = -half_height + radius;
+                velocity.y = -velocity.y * restitution;
+            } else if position.y + radius > half_height {
+                position.y = half_height - radius;
+                velocity.y = -velocity.y * restitution;
+            }
+        }
+    }
+
+    /// Separate and bounce the puck off a paddle, adding velocity transfer
+    /// when the contact starts
+    fn resolve_paddle(&mut self, player: Player) {
+        let index = player.index();
+        let paddle = self.paddles[index];
+        let min_distance = self.config.puck.radius + self.config.paddle.radius;
+
+        let offset = self.puck.position - paddle.position;
+        if offset.length_squared() >= min_distance * min_distance {
+            self.paddle_contact[index] = false;
+            return;
+        }
+
+        let distance = offset.length();
+        let normal = if distance < OVERLAP_EPSILON {
+            // Push the puck toward the opponent's side
+            let y = if paddle.position.y > Fixed::ZERO {
+                -Fixed::ONE
+            } else {
+                Fixed::ONE
+            };
+            Vec2::new(Fixed::ZERO, y)
+        } else {
+            Vec2::new(offset.x / distance, offset.y / distance)
+        };
+
+        self.puck.position = paddle.position + normal.scale(min_distance);
+
+        // Paddles are position-controlled, so only the puck reacts
+        let restitution = self
+            .config
+            .puck
+            .restitution
+            .max(self.config.paddle.restitution);
+        let approach = (self.puck.velocity - paddle.velocity).dot(normal);
+        if approach < Fixed::ZERO {
+            self.puck.velocity =
+                self.puck.velocity - normal.scale(approach * (Fixed::ONE + restitution));
+        }
+
+        if !self.paddle_contact[index] {
+            let transfer = paddle.velocity.length() * self.config.paddle.velocity_transfer;
+            self.puck.velocity += normal.scale(transfer);
+        }
+        self.paddle_contact[index] = true;
+    }
+
+    /// Player 1 scores through the top goal, player 2 through the bottom one
+    fn check_goal(&self) -> Option<Player> {
+        let half_height = self.config.table.height / Fixed::from_int(2);
+        let half_goal = self.config.table.goal_width / Fixed::from_int(2);
+        let radius = self.config.puck.radius;
+        let position = self.puck.position;
+
+        if position.x.abs() > half_goal {
+            return None;
+        }
+        if position.y < -(half_height + radius) {
+            Some(Player::One)
+        } else if position.y > half_height + radius {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+
+    /// Place the puck, at rest, in the receiving player's half
+    fn reset_puck(&mut self, serve_toward: Player) {
+        let offset = self.config.table.height / Fixed::from_int(4);
+        let y = match serve_toward {
+            Player::One => offset,
+            Player::Two => -offset,
+        };
+        self.puck = Body {
+            position: Vec2::new(Fixed::ZERO, y),
+            velocity: Vec2::ZERO,
+        };
+        self.paddle_contact = [false, false];
+    }
+}
+
+/// Replay an input log, sorted by tick, until a player wins or `max_ticks` is reached
+pub fn replay(config: PhysicsConfig, inputs: &[InputFrame], max_ticks: u32) -> ReplayOutcome {
+    let mut simulation = Simulation::new(config);
+    let mut pending = inputs.iter().peekable();
+
+    while !simulation.is_finished() && simulation.tick() < max_ticks {
+        while let Some(frame) = pending.next_if(|frame| frame.tick <= simulation.tick()) {
+            simulation.set_paddle_target(frame.player, frame.target);
+        }
+        simulation.step();
+    }
+
+    let (player1_score, player2_score) = simulation.score();
+    ReplayOutcome {
+        player1_score,
+        player2_score,
+        ticks: simulation.tick(),
+        goals: simulation.goals,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PHYSICS_CONFIG;
+
+    /// Player 1 strikes the puck straight up the table while player 2
+    /// stands aside, which scores within a few dozen ticks
+    fn strike_inputs() -> Vec<InputFrame> {
+        vec![
+            InputFrame {
+                tick: 0,
+                player: Player::Two,
+                target: Vec2::new(Fixed::from_int(200), Fixed::from_int(-100)),
+            },
+            InputFrame {
+                tick: 0,
+                player: Player::One,
+                target: Vec2::new(Fixed::ZERO, Fixed::from_int(40)),
+            },
+        ]
+    }
+
+    /// A longer rally with both paddles chasing around the table
+    fn rally_inputs() -> Vec<InputFrame> {
+        (0..600u32)
+            .flat_map(|tick| {
+                let wobble = Fixed::from_int(i64::from(tick % 90) * 3 - 135);
+                [
+                    InputFrame {
+                        tick,
+                        player: Player::One,
+                        target: Vec2::new(wobble, Fixed::from_int(60 + i64::from(tick % 40))),
+                    },
+                    InputFrame {
+                        tick,
+                        player: Player::Two,
+                        target: Vec2::new(-wobble, Fixed::from_int(-60 - i64::from(tick % 50))),
+                    },
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let inputs = rally_inputs();
+        let first = replay(PHYSICS_CONFIG, &inputs, 5_000);
+        let second = replay(PHYSICS_CONFIG, &inputs, 5_000);
+        assert_eq!(first, second);
+
+        let goals_by = |scorer| {
+            first
+                .goals
+                .iter()
+                .filter(|goal| goal.scorer == scorer)
+                .count()
+        };
+        assert_eq!(goals_by(Player::One), usize::from(first.player1_score));
+        assert_eq!(goals_by(Player::Two), usize::from(first.player2_score));
+    }
+
+    #[test]
+    fn replay_matches_stepping_the_simulation() {
+        let inputs = strike_inputs();
+        let outcome = replay(PHYSICS_CONFIG, &inputs, 100);
+
+        let mut simulation = Simulation::new(PHYSICS_CONFIG);
+        for frame in &inputs {
+            simulation.set_paddle_target(frame.player, frame.target);
+        }
+        for _ in 0..outcome.ticks {
+            simulation.step();
+        }
+
+        assert_eq!(
+            simulation.score(),
+            (outcome.player1_score, outcome.player2_score)
+        );
+        assert_eq!(simulation.goals(), outcome.goals.as_slice());
+    }
+
+    #[test]
+    fn puck_through_the_top_goal_scores_for_player_one() {
+        let outcome = replay(PHYSICS_CONFIG, &strike_inputs(), 100);
+        assert_eq!((outcome.player1_score, outcome.player2_score), (1, 0));
+        assert_eq!(outcome.goals.len(), 1);
+        assert_eq!(outcome.goals[0].scorer, Player::One);
+    }
+
+    #[test]
+    fn puck_outside_the_mouth_bounces_off_the_goal_line() {
+        let mut simulation = Simulation::new(PHYSICS_CONFIG);
+        simulation.puck = Body {
+            position: Vec2::new(Fixed::from_int(200), Fixed::from_int(-350)),
+            velocity: Vec2::new(Fixed::ZERO, Fixed::from_int(-20)),
+        };
+
+        simulation.step();
+
+        assert!(simulation.goals().is_empty());
+        assert!(simulation.puck().velocity.y > Fixed::ZERO);
+    }
+
+    #[test]
+    fn puck_rests_off_table_after_a_goal_then_serves_to_the_conceding_player() {
+        let mut simulation = Simulation::new(PHYSICS_CONFIG);
+        simulation.puck = Body {
+            position: Vec2::new(Fixed::ZERO, Fixed::from_int(380)),
+            velocity: Vec2::new(Fixed::ZERO, Fixed::from_int(20)),
+        };
+
+        simulation.step();
+        assert_eq!(simulation.score(), (0, 1));
+        let resting = simulation.puck();
+        assert_eq!(resting.velocity, Vec2::ZERO);
+
+        // The puck stays put for the whole pause, even with paddles moving
+        let pause = PHYSICS_CONFIG.game.goal_pause_ticks();
+        simulation.set_paddle_target(Player::One, Vec2::new(Fixed::ZERO, Fixed::from_int(100)));
+        for _ in 1..pause {
+            simulation.step();
+            assert_eq!(simulation.puck(), resting);
+        }
+
+        // Player 1 conceded, so the puck is served into their half
+        simulation.step();
+        let quarter = PHYSICS_CONFIG.table.height / Fixed::from_int(4);
+        assert_eq!(
+            simulation.puck(),
+            Body {
+                position: Vec2::new(Fixed::ZERO, quarter),
+                velocity: Vec2::ZERO
+            }
+        );
+        assert_eq!(simulation.score(), (0, 1));
+    }
+}
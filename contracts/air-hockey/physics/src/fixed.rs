@@ -0,0 +1,177 @@
+//! Fixed-point arithmetic
+//!
+//! All simulation math goes through `Fixed` so a replay produces the same
+//! result on every platform, with no floating point involved.
+
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+/// Signed fixed-point number with 16 fractional bits
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    /// Number of fractional bits
+    pub const FRAC_BITS: u32 = 16;
+    /// Zero
+    pub const ZERO: Fixed = Fixed(0);
+    /// One
+    pub const ONE: Fixed = Fixed(1 << Self::FRAC_BITS);
+
+    /// Create from a raw fixed-point representation
+    pub const fn from_raw(raw: i64) -> Self {
+        Fixed(raw)
+    }
+
+    /// Get the raw fixed-point representation
+    pub const fn raw(self) -> i64 {
+        self.0
+    }
+
+    /// Create from a whole number
+    pub const fn from_int(value: i64) -> Self {
+        Fixed(value << Self::FRAC_BITS)
+    }
+
+    /// Create from a fraction, e.g. `from_ratio(98, 100)` for 0.98
+    pub const fn from_ratio(numerator: i64, denominator: i64) -> Self {
+        Fixed((numerator << Self::FRAC_BITS) / denominator)
+    }
+
+    /// Round down to a whole number
+    pub const fn to_int(self) -> i64 {
+        self.0 >> Self::FRAC_BITS
+    }
+
+    /// Absolute value
+    pub const fn abs(self) -> Self {
+        Fixed(self.0.abs())
+    }
+
+    /// Square root, zero for negative inputs
+    pub fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Self::ZERO;
+        }
+        Fixed(((self.0 as u128) << Self::FRAC_BITS).isqrt() as i64)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((i128::from(self.0) * i128::from(rhs.0)) >> Self::FRAC_BITS) as i64)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    /// Divide, panicking on a zero divisor like integer division
+    fn div(self, rhs: Fixed) -> Fixed {
+        Fixed(((i128::from(self.0) << Self::FRAC_BITS) / i128::from(rhs.0)) as i64)
+    }
+}
+
+/// Two-dimensional fixed-point vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Vec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl Vec2 {
+    /// Zero vector
+    pub const ZERO: Vec2 = Vec2 {
+        x: Fixed::ZERO,
+        y: Fixed::ZERO,
+    };
+
+    /// Create a vector
+    pub const fn new(x: Fixed, y: Fixed) -> Self {
+        Self { x, y }
+    }
+
+    /// Dot product
+    pub fn dot(self, other: Vec2) -> Fixed {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Squared length, cheaper than `length` for comparisons
+    pub fn length_squared(self) -> Fixed {
+        self.dot(self)
+    }
+
+    /// Length
+    pub fn length(self) -> Fixed {
+        self.length_squared().sqrt()
+    }
+
+    /// Multiply both components by a scalar
+    pub fn scale(self, factor: Fixed) -> Vec2 {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Vec2) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Vec2 {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
@@ -0,0 +1,15 @@
+//! Air Hockey Deterministic Physics
+//!
+//! Fixed-point air hockey simulation shared by the Linera contract and
+//! off-chain tools. Replaying the same paddle input log always produces the
+//! same goals and final score.
+
+mod config;
+mod engine;
+mod fixed;
+
+pub use config::{
+    GameConfig, PaddleConfig, PhysicsConfig, PuckConfig, TableConfig, WallConfig, PHYSICS_CONFIG,
+};
+pub use engine::{replay, Body, Goal, InputFrame, Player, ReplayOutcome, Simulation};
+pub use fixed::{Fixed, Vec2};
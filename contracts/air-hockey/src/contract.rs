@@ -7,14 +7,63 @@
 
 mod state;
 
-use air_hockey::{AirHockeyAbi, InstantiationArgument, Message, Operation};
+use air_hockey::{
+    AirHockeyAbi, BetOutcome, CollusionLimits, GoalEvent, InstantiationArgument, Message,
+    Operation, StakeCurrency, MAX_TIME_LIMIT_SECS,
+};
+use air_hockey_physics::{replay, InputFrame, Player, PHYSICS_CONFIG};
 use linera_sdk::{
     abi::WithContractAbi,
+    linera_base_types::{Amount, CryptoHash},
     views::{RootView, View},
     Contract, ContractRuntime,
 };
 
-use crate::state::{AirHockeyState, Game, GameStatus};
+use crate::state::{
+    pair_key, Achievement, AirHockeyState, Badge, BracketMatch, Challenge, ChallengeStatus, Game,
+    GameStatus, PendingResult, PlayerBadges, PlayerProfile, PlayerStats, Quest, QuestProgress,
+    ReceivedChallenge, Reconciliation, ReplayVerdict, RewardTotals, Season, SeasonStanding, Series,
+    SideBet, SidePool, Sponsorship, Tournament, SCHEMA_VERSION,
+};
+
+/// How long an active game can wait for its result after its time limit,
+/// or after it started when it has none, before it can be expired (2 hours)
+const ACTIVE_GAME_TIMEOUT_MICROS: u64 = 2 * 60 * 60 * 1_000_000;
+
+/// How long after a game starts spectators can still bet on it (2 minutes)
+const BET_CUTOFF_MICROS: u64 = 2 * 60 * 1_000_000;
+
+/// Maximum number of bets in one game's side pool
+const MAX_SIDE_BETS: usize = 200;
+
+/// Maximum number of sponsors of one game or tournament
+const MAX_SPONSORS: usize = 50;
+
+/// Maximum length of a quest name
+const MAX_QUEST_NAME_LENGTH: usize = 64;
+
+/// Maximum number of quests tracked at once
+const MAX_ACTIVE_QUESTS: usize = 20;
+
+/// Consecutive ranked wins that earn `Achievement::WinStreak`
+const WIN_STREAK_BADGE: i32 = 10;
+
+/// Games played that earn `Achievement::Centurion`
+const CENTURION_BADGE_GAMES: u64 = 100;
+
+/// How long finished games keep their full record before they can be archived (30 days)
+const ARCHIVE_RETENTION_MICROS: u64 = 30 * 24 * 60 * 60 * 1_000_000;
+
+/// Number of completed game IDs kept in `AirHockeyState::recent_completed_games`
+const RECENT_COMPLETED_GAMES: usize = 50;
+
+/// Longest replay of a disputed game, sized to fit one block's execution
+/// budget (`MAX_TIME_LIMIT_SECS` at 60 ticks per second); untimed games
+/// still undecided by then are voided
+const MAX_REPLAY_TICKS: u32 = MAX_TIME_LIMIT_SECS * 60;
+
+/// Most input frames a player can reveal for one disputed game
+const MAX_INPUT_FRAMES: usize = 12_000;
 
 pub struct AirHockeyContract {
     state: AirHockeyState,
@@ -41,39 +90,61 @@ impl Contract for AirHockeyContract {
     }
 
     async fn instantiate(&mut self, argument: Self::InstantiationArgument) {
+        self.state.schema_version.set(SCHEMA_VERSION);
+        self.state.migration_cursor.set(1);
+        self.state.archive_cursor.set(1);
         self.state.owner.set(argument.owner);
         self.state.next_game_id.set(1);
-        self.state.total_stake_pool.set(0);
+        self.state.next_series_id.set(1);
+        self.state.next_tournament_id.set(1);
+        self.state.next_season_id.set(1);
+        self.state.next_quest_id.set(1);
+        self.state.next_challenge_id.set(1);
+        self.state.current_season.set(None);
+        self.state.collusion_limits.set(CollusionLimits::default());
     }
 
     async fn execute_operation(&mut self, operation: Self::Operation) -> Self::Response {
         let timestamp = self.runtime.system_time().micros();
-        let caller = self.runtime.authenticated_signer()
+        let caller = self
+            .runtime
+            .authenticated_signer()
             .map(|o| o.to_string())
             .unwrap_or_else(|| "anonymous".to_string());
 
+        self.upgrade_schema();
+
         match operation {
-            Operation::CreateGame { stake, room_code } => {
+            Operation::CreateGame {
+                stake,
+                currency,
+                room_code,
+                rules,
+                min_reliability,
+            } => {
+                if !rules.is_valid() || min_reliability.is_some_and(|min| min > 1000) {
+                    return 0;
+                }
+
                 // Get next game ID
                 let id = *self.state.next_game_id.get();
                 self.state.next_game_id.set(id + 1);
 
                 // Create game
-                let game = Game::new(id, caller.clone(), stake, room_code.clone(), timestamp);
-                self.state.games.insert(&id, game).expect("Failed to insert game");
+                let mut game = Game::new(id, caller.clone(), stake, room_code.clone(), timestamp);
+                game.currency = currency;
+                game.rules = rules;
+                game.min_reliability = min_reliability;
+                self.save_game(game).await;
 
-                // Update total stake pool
-                let current_pool = *self.state.total_stake_pool.get();
-                self.state.total_stake_pool.set(current_pool + stake);
+                // Update stake pool
+                self.add_to_stake_pool(currency, stake).await;
 
                 id
             }
 
             Operation::JoinGame { game_id } => {
-                let mut game = self.state.games.get(&game_id)
-                    .await
-                    .expect("Failed to get game")
-                    .expect("Game not found");
+                let mut game = self.state.load_game(game_id).await.expect("Game not found");
 
                 if !game.can_join() {
                     return 0;
@@ -83,15 +154,29 @@ impl Contract for AirHockeyContract {
                     return 0; // Cannot join own game
                 }
 
+                if self.is_blocked_pair(&game.creator, &caller).await {
+                    return 0;
+                }
+
+                if let Some(min_reliability) = game.min_reliability {
+                    let stats = self
+                        .state
+                        .load_player_stats(&caller)
+                        .await
+                        .unwrap_or_default();
+                    if stats.reliability() < u32::from(min_reliability) {
+                        return 0; // Not reliable enough for this creator
+                    }
+                }
+
                 game.opponent = Some(caller);
                 game.status = GameStatus::Active;
                 game.started_at = Some(timestamp);
 
-                self.state.games.insert(&game_id, game.clone()).expect("Failed to update game");
+                self.save_game(game.clone()).await;
 
-                // Update total stake pool
-                let current_pool = *self.state.total_stake_pool.get();
-                self.state.total_stake_pool.set(current_pool + game.stake);
+                // Update stake pool
+                self.add_to_stake_pool(game.currency, game.stake).await;
 
                 game_id
             }
@@ -100,52 +185,94 @@ impl Contract for AirHockeyContract {
                 game_id,
                 player1_score,
                 player2_score,
+                replay_hash,
+                timeline,
             } => {
-                let mut game = self.state.games.get(&game_id)
-                    .await
-                    .expect("Failed to get game")
-                    .expect("Game not found");
+                let game = self.state.load_game(game_id).await.expect("Game not found");
 
                 if !game.is_active() {
                     return 0;
                 }
 
                 // Verify caller is a participant
-                if game.creator != caller && game.opponent.as_ref() != Some(&caller) {
+                if game.player_index(&caller).is_none() {
                     return 0;
                 }
 
-                game.player1_score = player1_score;
-                game.player2_score = player2_score;
-                game.status = GameStatus::Completed;
-                game.ended_at = Some(timestamp);
+                if !game.rules.is_possible_score(player1_score, player2_score) {
+                    return 0; // Score cannot happen under the game rules
+                }
 
-                // Determine winner
-                game.winner = if player1_score > player2_score {
-                    Some(game.creator.clone())
-                } else if player2_score > player1_score {
-                    game.opponent.clone()
-                } else {
-                    None // Draw
-                };
+                if timeline.as_ref().is_some_and(|timeline| {
+                    !GoalEvent::is_consistent(timeline, player1_score, player2_score)
+                }) {
+                    return 0; // Timeline does not add up to the submitted score
+                }
+
+                let pending = self
+                    .state
+                    .pending_results
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to get pending result");
+                match pending {
+                    None => {
+                        let pending = PendingResult {
+                            player1_score,
+                            player2_score,
+                            submitted_by: caller,
+                            submitted_at: timestamp,
+                            replay_hash,
+                            timeline,
+                        };
+                        self.state
+                            .pending_results
+                            .insert(&game_id, pending)
+                            .expect("Failed to store pending result");
+                    }
+                    Some(pending) if pending.submitted_by == caller => return 0, // Already submitted
+                    Some(pending)
+                        if (pending.player1_score, pending.player2_score)
+                            == (player1_score, player2_score) =>
+                    {
+                        // Both players agree on the score
+                        self.settle_pending_result(game, pending, timestamp).await;
+                    }
+                    // Conflicting scores can only be resolved by replay
+                    Some(_) => self.open_dispute(game, caller, timestamp).await,
+                }
+
+                game_id
+            }
+
+            Operation::FinalizeResult { game_id } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
 
-                // Update player stats
-                self.update_player_stats(&game).await;
+                if !game.is_active() {
+                    return 0;
+                }
+
+                let Some(pending) = self
+                    .state
+                    .pending_results
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to get pending result")
+                else {
+                    return 0;
+                };
 
-                // Update total stake pool (remove both stakes)
-                let current_pool = *self.state.total_stake_pool.get();
-                self.state.total_stake_pool.set(current_pool.saturating_sub(game.total_pot()));
+                if timestamp <= pending.confirmation_deadline() {
+                    return 0; // Still open to dispute
+                }
 
-                self.state.games.insert(&game_id, game).expect("Failed to update game");
+                self.settle_pending_result(game, pending, timestamp).await;
 
                 game_id
             }
 
             Operation::CancelGame { game_id } => {
-                let mut game = self.state.games.get(&game_id)
-                    .await
-                    .expect("Failed to get game")
-                    .expect("Game not found");
+                let mut game = self.state.load_game(game_id).await.expect("Game not found");
 
                 if game.status != GameStatus::Waiting {
                     return 0; // Can only cancel waiting games
@@ -158,69 +285,3141 @@ impl Contract for AirHockeyContract {
                 game.status = GameStatus::Cancelled;
                 game.ended_at = Some(timestamp);
 
-                // Update total stake pool
-                let current_pool = *self.state.total_stake_pool.get();
-                self.state.total_stake_pool.set(current_pool.saturating_sub(game.stake));
+                // Update stake pool
+                self.remove_from_stake_pool(game.currency, game.stake).await;
+                self.close_game_sponsorship(game_id, None, timestamp).await;
 
-                self.state.games.insert(&game_id, game).expect("Failed to update game");
+                self.save_game(game).await;
 
                 game_id
             }
-        }
-    }
 
-    async fn execute_message(&mut self, _message: Self::Message) {
-        // Handle cross-chain messages if needed
-        // For now, messages are just notifications
-    }
+            Operation::PlaceBet {
+                game_id,
+                outcome,
+                amount,
+            } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
 
-    async fn store(mut self) {
-        self.state.save().await.expect("Failed to save state");
-    }
-}
+                if game.status != GameStatus::Active || amount.is_zero() {
+                    return 0;
+                }
 
-impl AirHockeyContract {
-    /// Update player statistics after a game
-    async fn update_player_stats(&mut self, game: &Game) {
-        let opponent = match game.opponent.as_ref() {
-            Some(o) => o.clone(),
-            None => return,
-        };
+                if game.player_index(&caller).is_some() {
+                    return 0; // Players cannot bet on their own game
+                }
 
-        // Update creator stats
-        let mut creator_stats = self.state.player_stats.get(&game.creator)
-            .await
-            .expect("Failed to get stats")
-            .unwrap_or_default();
+                if self
+                    .state
+                    .pending_results
+                    .contains_key(&game_id)
+                    .await
+                    .expect("Failed to get pending result")
+                {
+                    return 0; // Result already submitted
+                }
 
-        creator_stats.games_played += 1;
+                let started_at = game.started_at.unwrap_or(game.created_at);
+                if timestamp > started_at + BET_CUTOFF_MICROS {
+                    return 0; // Betting closed
+                }
 
-        if game.winner.as_ref() == Some(&game.creator) {
-            creator_stats.wins += 1;
-            creator_stats.tokens_won += game.stake;
-        } else if game.winner.is_some() {
-            creator_stats.losses += 1;
-            creator_stats.tokens_lost += game.stake;
-        }
+                let mut pool = self
+                    .state
+                    .side_pools
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to get side pool")
+                    .unwrap_or_else(|| SidePool::new(game_id, game.currency));
+                if pool.bets.len() >= MAX_SIDE_BETS {
+                    return 0;
+                }
 
-        self.state.player_stats.insert(&game.creator, creator_stats).expect("Failed to update stats");
+                pool.bets.push(SideBet {
+                    bettor: caller,
+                    outcome,
+                    amount,
+                    payout: None,
+                    placed_at: timestamp,
+                });
+                self.state
+                    .side_pools
+                    .insert(&game_id, pool)
+                    .expect("Failed to update side pool");
 
-        // Update opponent stats
-        let mut opponent_stats = self.state.player_stats.get(&opponent)
-            .await
-            .expect("Failed to get stats")
-            .unwrap_or_default();
+                // Update stake pool
+                self.add_to_stake_pool(game.currency, amount).await;
+
+                game_id
+            }
 
-        opponent_stats.games_played += 1;
+            Operation::SponsorGame { game_id, amount } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
 
-        if game.winner.as_ref() == Some(&opponent) {
-            opponent_stats.wins += 1;
-            opponent_stats.tokens_won += game.stake;
-        } else if game.winner.is_some() {
-            opponent_stats.losses += 1;
-            opponent_stats.tokens_lost += game.stake;
-        }
+                if !matches!(game.status, GameStatus::Waiting | GameStatus::Active)
+                    || amount.is_zero()
+                {
+                    return 0;
+                }
+
+                let mut sponsorship = self
+                    .state
+                    .game_sponsorships
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to get sponsorship")
+                    .unwrap_or_else(|| Sponsorship::new(game.currency));
+                let new_sponsor = !sponsorship
+                    .sponsors
+                    .iter()
+                    .any(|(sponsor, _)| *sponsor == caller);
+                if new_sponsor && sponsorship.sponsors.len() >= MAX_SPONSORS {
+                    return 0;
+                }
+
+                sponsorship.add(caller, amount);
+                self.state
+                    .game_sponsorships
+                    .insert(&game_id, sponsorship)
+                    .expect("Failed to update sponsorship");
+
+                // Update stake pool
+                self.add_to_stake_pool(game.currency, amount).await;
+
+                game_id
+            }
+
+            Operation::DisputeResult { game_id } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if !game.is_active() {
+                    return 0; // Only games without a settled result can be disputed
+                }
+
+                if game.player_index(&caller).is_none() {
+                    return 0;
+                }
+
+                if let Some(pending) = self
+                    .state
+                    .pending_results
+                    .get(&game_id)
+                    .await
+                    .expect("Failed to get pending result")
+                {
+                    if pending.submitted_by == caller {
+                        return 0; // Players cannot dispute their own result
+                    }
+                    if timestamp > pending.confirmation_deadline() {
+                        return 0; // Confirmation window closed
+                    }
+                }
+
+                self.open_dispute(game, caller, timestamp).await;
+
+                game_id
+            }
+
+            Operation::CommitInputLog {
+                game_id,
+                commitment,
+            } => {
+                let mut game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if game.status != GameStatus::Disputed || Self::dispute_expired(&game, timestamp) {
+                    return 0;
+                }
+
+                let Some(index) = game.player_index(&caller) else {
+                    return 0;
+                };
+
+                if game.input_commitments[index].is_some() {
+                    return 0; // Commitments cannot be replaced
+                }
+
+                game.input_commitments[index] = Some(commitment);
+                self.save_game(game).await;
+
+                game_id
+            }
+
+            Operation::RevealInputLog { log } => {
+                let game_id = log.game_id;
+                let game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if game.status != GameStatus::Disputed || Self::dispute_expired(&game, timestamp) {
+                    return 0;
+                }
+
+                // Both players must commit before anyone reveals
+                let [Some(commitment1), Some(commitment2)] = game.input_commitments else {
+                    return 0;
+                };
+
+                let Some(index) = game.player_index(&caller) else {
+                    return 0;
+                };
+
+                let commitment = if index == 0 { commitment1 } else { commitment2 };
+                if CryptoHash::new(&log) != commitment {
+                    return 0; // Log does not match the commitment
+                }
+
+                if log.frames.len() > MAX_INPUT_FRAMES {
+                    return 0; // Too long to replay
+                }
+
+                // Players can only reveal inputs for their own paddle, within the replayed ticks
+                let player = if index == 0 { Player::One } else { Player::Two };
+                let max_ticks = Self::replay_ticks(&game);
+                if log
+                    .frames
+                    .iter()
+                    .any(|frame| frame.player != player || frame.tick >= max_ticks)
+                {
+                    return 0;
+                }
+
+                let key = (game_id, index as u8);
+                if self
+                    .state
+                    .revealed_inputs
+                    .contains_key(&key)
+                    .await
+                    .expect("Failed to get inputs")
+                {
+                    return 0; // Already revealed
+                }
+                self.state
+                    .revealed_inputs
+                    .insert(&key, log.frames)
+                    .expect("Failed to store inputs");
+
+                self.replay_dispute(game, timestamp).await;
+
+                game_id
+            }
+
+            Operation::ClaimDisputeTimeout { game_id } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if game.status != GameStatus::Disputed || !Self::dispute_expired(&game, timestamp) {
+                    return 0;
+                }
+
+                if game.player_index(&caller).is_none() {
+                    return 0;
+                }
+
+                // Rank each side by how far it got: revealed, committed or nothing
+                let mut progress = [0u8; 2];
+                for index in 0..2u8 {
+                    let revealed = self
+                        .state
+                        .revealed_inputs
+                        .contains_key(&(game_id, index))
+                        .await
+                        .expect("Failed to get inputs");
+                    self.state
+                        .revealed_inputs
+                        .remove(&(game_id, index))
+                        .expect("Failed to remove inputs");
+                    progress[index as usize] = if revealed {
+                        2
+                    } else {
+                        u8::from(game.input_commitments[index as usize].is_some())
+                    };
+                }
+
+                // The side that got further wins by forfeit; on a tie the
+                // disputer failed to back their claim and forfeits
+                let loser = match progress[0].cmp(&progress[1]) {
+                    std::cmp::Ordering::Less => 0,
+                    std::cmp::Ordering::Greater => 1,
+                    std::cmp::Ordering::Equal => match game
+                        .disputed_by
+                        .as_deref()
+                        .and_then(|disputer| game.player_index(disputer))
+                    {
+                        Some(index) => index,
+                        None => {
+                            self.void_game(game, timestamp).await;
+                            return game_id;
+                        }
+                    },
+                };
+                self.forfeit_game(game, loser, timestamp).await;
+
+                game_id
+            }
+
+            Operation::ForfeitGame { game_id } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if !game.is_active() {
+                    return 0;
+                }
+
+                let Some(index) = game.player_index(&caller) else {
+                    return 0;
+                };
+                self.forfeit_game(game, index, timestamp).await;
+
+                game_id
+            }
+
+            Operation::ExpireGame { game_id } => {
+                let game = self.state.load_game(game_id).await.expect("Game not found");
+
+                if !game.is_active() {
+                    return 0;
+                }
+
+                if game.player_index(&caller).is_none() {
+                    return 0;
+                }
+
+                if self
+                    .state
+                    .pending_results
+                    .contains_key(&game_id)
+                    .await
+                    .expect("Failed to get pending result")
+                {
+                    return 0; // A result arrived; finalize or dispute it instead
+                }
+
+                let started_at = game.started_at.unwrap_or(game.created_at);
+                let match_length = game
+                    .rules
+                    .time_limit_secs
+                    .map_or(0, |secs| u64::from(secs) * 1_000_000);
+                if timestamp <= started_at + match_length + ACTIVE_GAME_TIMEOUT_MICROS {
+                    return 0; // Result may still arrive
+                }
+
+                // Nobody can tell who walked away, so both players take the hit
+                let players: Vec<String> = std::iter::once(game.creator.clone())
+                    .chain(game.opponent.clone())
+                    .collect();
+                self.void_game(game, timestamp).await;
+                for player in &players {
+                    self.update_lifetime_stats(player, |stats| stats.expired_games += 1)
+                        .await;
+                }
+
+                game_id
+            }
+
+            Operation::CreateSeries {
+                stake,
+                currency,
+                room_code,
+                best_of,
+            } => {
+                if best_of == 0 || best_of % 2 == 0 {
+                    return 0; // Series length must be odd
+                }
+
+                // Get next series ID
+                let id = *self.state.next_series_id.get();
+                self.state.next_series_id.set(id + 1);
+
+                // Create series
+                let mut series = Series::new(id, caller, stake, best_of, room_code, timestamp);
+                series.currency = currency;
+                self.state
+                    .series
+                    .insert(&id, series)
+                    .expect("Failed to insert series");
+
+                // Update stake pool
+                self.add_to_stake_pool(currency, stake).await;
+
+                id
+            }
+
+            Operation::JoinSeries { series_id } => {
+                let mut series = self
+                    .state
+                    .series
+                    .get(&series_id)
+                    .await
+                    .expect("Failed to get series")
+                    .expect("Series not found");
+
+                if !series.can_join() {
+                    return 0;
+                }
+
+                if series.creator == caller {
+                    return 0; // Cannot join own series
+                }
+
+                if self.is_blocked_pair(&series.creator, &caller).await {
+                    return 0;
+                }
+
+                series.opponent = Some(caller);
+                series.status = GameStatus::Active;
+                series.started_at = Some(timestamp);
+
+                // Update stake pool
+                self.add_to_stake_pool(series.currency, series.stake).await;
+
+                self.start_series_game(&mut series, timestamp).await;
+                self.state
+                    .series
+                    .insert(&series_id, series)
+                    .expect("Failed to update series");
+
+                series_id
+            }
+
+            Operation::CancelSeries { series_id } => {
+                let mut series = self
+                    .state
+                    .series
+                    .get(&series_id)
+                    .await
+                    .expect("Failed to get series")
+                    .expect("Series not found");
+
+                if series.status != GameStatus::Waiting {
+                    return 0; // Can only cancel waiting series
+                }
+
+                if series.creator != caller {
+                    return 0; // Only creator can cancel
+                }
+
+                series.status = GameStatus::Cancelled;
+                series.ended_at = Some(timestamp);
+
+                // Update stake pool
+                self.remove_from_stake_pool(series.currency, series.stake)
+                    .await;
+
+                self.state
+                    .series
+                    .insert(&series_id, series)
+                    .expect("Failed to update series");
+
+                series_id
+            }
+
+            Operation::CreateTournament {
+                entry_fee,
+                currency,
+                size,
+                prize_split,
+            } => {
+                if size < 2 || !size.is_power_of_two() {
+                    return 0; // Bracket needs a power-of-two field
+                }
+
+                if prize_split.is_empty()
+                    || prize_split.len() > usize::from(size)
+                    || prize_split.iter().map(|&p| u32::from(p)).sum::<u32>() != 100
+                {
+                    return 0; // Prize split must pay out the whole pool
+                }
+
+                // Get next tournament ID
+                let id = *self.state.next_tournament_id.get();
+                self.state.next_tournament_id.set(id + 1);
+
+                let mut tournament =
+                    Tournament::new(id, caller, entry_fee, size, prize_split, timestamp);
+                tournament.currency = currency;
+                self.state
+                    .tournaments
+                    .insert(&id, tournament)
+                    .expect("Failed to insert tournament");
+
+                id
+            }
+
+            Operation::RegisterTournament { tournament_id } => {
+                let mut tournament = self
+                    .state
+                    .tournaments
+                    .get(&tournament_id)
+                    .await
+                    .expect("Failed to get tournament")
+                    .expect("Tournament not found");
+
+                if !tournament.can_register(&caller) {
+                    return 0;
+                }
+
+                // The bracket may pair any two entrants
+                for player in &tournament.players {
+                    if self.is_blocked_pair(player, &caller).await {
+                        return 0;
+                    }
+                }
+
+                tournament.players.push(caller);
+
+                // Update stake pool
+                self.add_to_stake_pool(tournament.currency, tournament.entry_fee)
+                    .await;
+
+                if tournament.is_full() {
+                    self.start_tournament(&mut tournament, timestamp).await;
+                }
+
+                self.state
+                    .tournaments
+                    .insert(&tournament_id, tournament)
+                    .expect("Failed to update tournament");
+
+                tournament_id
+            }
+
+            Operation::CancelTournament { tournament_id } => {
+                let mut tournament = self
+                    .state
+                    .tournaments
+                    .get(&tournament_id)
+                    .await
+                    .expect("Failed to get tournament")
+                    .expect("Tournament not found");
+
+                if tournament.status != GameStatus::Waiting {
+                    return 0; // Can only cancel before the bracket is seeded
+                }
+
+                if tournament.organizer != caller {
+                    return 0; // Only organizer can cancel
+                }
 
-        self.state.player_stats.insert(&opponent, opponent_stats).expect("Failed to update stats");
+                tournament.status = GameStatus::Cancelled;
+                tournament.ended_at = Some(timestamp);
+
+                // Update stake pool (refund all entry fees)
+                self.remove_from_stake_pool(tournament.currency, tournament.prize_pool())
+                    .await;
+                self.close_tournament_sponsorship(tournament_id, None, timestamp)
+                    .await;
+
+                if tournament.players.iter().any(|player| *player != caller) {
+                    self.update_lifetime_stats(&caller, |stats| stats.late_cancellations += 1)
+                        .await;
+                }
+
+                self.state
+                    .tournaments
+                    .insert(&tournament_id, tournament)
+                    .expect("Failed to update tournament");
+
+                tournament_id
+            }
+
+            Operation::SponsorTournament {
+                tournament_id,
+                amount,
+            } => {
+                let tournament = self
+                    .state
+                    .tournaments
+                    .get(&tournament_id)
+                    .await
+                    .expect("Failed to get tournament")
+                    .expect("Tournament not found");
+
+                if !matches!(tournament.status, GameStatus::Waiting | GameStatus::Active)
+                    || amount.is_zero()
+                {
+                    return 0;
+                }
+
+                let mut sponsorship = self
+                    .state
+                    .tournament_sponsorships
+                    .get(&tournament_id)
+                    .await
+                    .expect("Failed to get sponsorship")
+                    .unwrap_or_else(|| Sponsorship::new(tournament.currency));
+                let new_sponsor = !sponsorship
+                    .sponsors
+                    .iter()
+                    .any(|(sponsor, _)| *sponsor == caller);
+                if new_sponsor && sponsorship.sponsors.len() >= MAX_SPONSORS {
+                    return 0;
+                }
+
+                sponsorship.add(caller, amount);
+                self.state
+                    .tournament_sponsorships
+                    .insert(&tournament_id, sponsorship)
+                    .expect("Failed to update sponsorship");
+
+                // Update stake pool
+                self.add_to_stake_pool(tournament.currency, amount).await;
+
+                tournament_id
+            }
+
+            Operation::OpenSeason {
+                reward_pool,
+                reward_top_n,
+            } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can open seasons
+                }
+
+                if self.state.current_season.get().is_some() {
+                    return 0; // Close the current season first
+                }
+
+                // Get next season ID
+                let id = *self.state.next_season_id.get();
+                self.state.next_season_id.set(id + 1);
+
+                let season = Season::new(id, reward_pool, reward_top_n, timestamp);
+                self.state
+                    .seasons
+                    .insert(&id, season)
+                    .expect("Failed to insert season");
+                self.state.current_season.set(Some(id));
+
+                // Update stake pool (the reward pool is held until the season closes)
+                self.add_to_stake_pool(StakeCurrency::Native, reward_pool)
+                    .await;
+
+                id
+            }
+
+            Operation::ArchiveGames {
+                max_games,
+                hash_history,
+            } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can archive games
+                }
+
+                self.archive_games(max_games as usize, hash_history, timestamp)
+                    .await;
+
+                1
+            }
+
+            Operation::Reconcile => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can reconcile the stake pools
+                }
+
+                let drift = self.state.stake_pool_drift().await;
+                for entry in &drift {
+                    self.state
+                        .stake_pools
+                        .insert(&entry.currency, entry.expected)
+                        .expect("Failed to update stake pool");
+                }
+                self.state.last_reconciliation.set(Some(Reconciliation {
+                    reconciled_at: timestamp,
+                    drift,
+                }));
+
+                1
+            }
+
+            Operation::MigrateRecords { max_records } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can run migrations
+                }
+
+                self.migrate_records(max_records as usize).await;
+
+                1
+            }
+
+            Operation::SetCollusionLimits { limits } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can change collusion limits
+                }
+
+                if limits.window_secs == 0 {
+                    return 0;
+                }
+
+                self.state.collusion_limits.set(limits);
+
+                1
+            }
+
+            Operation::CloseSeason => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can close seasons
+                }
+
+                let Some(season_id) = *self.state.current_season.get() else {
+                    return 0;
+                };
+
+                self.close_season(season_id, timestamp).await;
+
+                season_id
+            }
+
+            Operation::DefineQuest {
+                name,
+                goal,
+                reward,
+                reward_pool,
+                period_secs,
+                duration_secs,
+            } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can define quests
+                }
+
+                if name.is_empty()
+                    || name.len() > MAX_QUEST_NAME_LENGTH
+                    || goal.target() == 0
+                    || reward.is_zero()
+                    || reward_pool < reward
+                    || period_secs == Some(0)
+                {
+                    return 0;
+                }
+
+                if self.state.active_quests.get().len() >= MAX_ACTIVE_QUESTS {
+                    return 0; // End a quest first
+                }
+
+                // Get next quest ID
+                let id = *self.state.next_quest_id.get();
+                self.state.next_quest_id.set(id + 1);
+
+                let quest = Quest {
+                    id,
+                    name,
+                    goal,
+                    reward,
+                    reward_pool,
+                    period_secs,
+                    starts_at: timestamp,
+                    ends_at: duration_secs
+                        .map(|secs| timestamp.saturating_add(secs.saturating_mul(1_000_000))),
+                    claims: 0,
+                };
+                self.state
+                    .quests
+                    .insert(&id, quest)
+                    .expect("Failed to create quest");
+                self.state.active_quests.get_mut().push(id);
+
+                // Update stake pool (quest rewards)
+                self.add_to_stake_pool(StakeCurrency::Native, reward_pool)
+                    .await;
+
+                id
+            }
+
+            Operation::EndQuest { quest_id } => {
+                if *self.state.owner.get() != caller {
+                    return 0; // Only owner can end quests
+                }
+
+                let Some(mut quest) = self
+                    .state
+                    .quests
+                    .get(&quest_id)
+                    .await
+                    .expect("Failed to get quest")
+                else {
+                    return 0;
+                };
+
+                let active = self.state.active_quests.get_mut();
+                let Some(position) = active.iter().position(|id| *id == quest_id) else {
+                    return 0; // Already ended
+                };
+                active.remove(position);
+
+                // Keep back the rewards players already earned and release
+                // the rest of the pool
+                let target = quest.goal.target();
+                let mut earned = 0u128;
+                self.state
+                    .quest_progress
+                    .for_each_index_value(|(id, _), progress| {
+                        if id == quest_id {
+                            earned += u128::from(progress.claimable(target));
+                        }
+                        Ok(())
+                    })
+                    .await
+                    .expect("Failed to scan quest progress");
+                let reserved = quest
+                    .reward
+                    .try_mul(earned)
+                    .unwrap_or(Amount::MAX)
+                    .min(quest.reward_pool);
+                let released = quest
+                    .reward_pool
+                    .try_sub(reserved)
+                    .expect("Reserved rewards exceed the pool");
+                quest.reward_pool = reserved;
+
+                if quest.is_running(timestamp) {
+                    quest.ends_at = Some(timestamp);
+                }
+                self.state
+                    .quests
+                    .insert(&quest_id, quest)
+                    .expect("Failed to update quest");
+
+                // Update stake pool (unearned quest rewards)
+                self.remove_from_stake_pool(StakeCurrency::Native, released)
+                    .await;
+
+                quest_id
+            }
+
+            Operation::ClaimQuestReward { quest_id } => {
+                let Some(mut quest) = self
+                    .state
+                    .quests
+                    .get(&quest_id)
+                    .await
+                    .expect("Failed to get quest")
+                else {
+                    return 0;
+                };
+
+                let key = (quest_id, caller.clone());
+                let Some(mut progress) = self
+                    .state
+                    .quest_progress
+                    .get(&key)
+                    .await
+                    .expect("Failed to get quest progress")
+                else {
+                    return 0;
+                };
+
+                // Progress from a finished period can still be claimed
+                if progress.claimable(quest.goal.target()) == 0 {
+                    return 0;
+                }
+
+                if quest.reward_pool < quest.reward {
+                    return 0; // Reward pool exhausted
+                }
+
+                quest.reward_pool = quest
+                    .reward_pool
+                    .try_sub(quest.reward)
+                    .expect("Reward exceeds the pool");
+                quest.claims += 1;
+                let reward = quest.reward;
+                self.update_reward_totals(&caller, |totals| {
+                    totals
+                        .quest_rewards
+                        .try_add_assign(reward)
+                        .expect("Reward overflow");
+                })
+                .await;
+
+                // Update stake pool (quest reward paid out)
+                self.remove_from_stake_pool(StakeCurrency::Native, reward)
+                    .await;
+
+                // Rewards banked from earlier periods are paid first
+                if progress.banked > 0 {
+                    progress.banked -= 1;
+                } else {
+                    progress.claimed = true;
+                }
+                self.state
+                    .quest_progress
+                    .insert(&key, progress)
+                    .expect("Failed to update quest progress");
+                self.state
+                    .quests
+                    .insert(&quest_id, quest)
+                    .expect("Failed to update quest");
+
+                quest_id
+            }
+
+            Operation::SetProfile {
+                nickname,
+                avatar,
+                country,
+            } => {
+                if !PlayerProfile::is_valid_nickname(&nickname)
+                    || !avatar.as_deref().is_none_or(PlayerProfile::is_valid_avatar)
+                    || !country
+                        .as_deref()
+                        .is_none_or(PlayerProfile::is_valid_country)
+                {
+                    return 0;
+                }
+
+                let key = PlayerProfile::nickname_key(&nickname);
+                let holder = self
+                    .state
+                    .nicknames
+                    .get(&key)
+                    .await
+                    .expect("Failed to get nickname");
+                if holder.is_some_and(|holder| holder != caller) {
+                    return 0; // Nickname taken
+                }
+
+                let existing = self
+                    .state
+                    .player_profiles
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get profile");
+                let created_at = match &existing {
+                    Some(profile) => {
+                        // Release the old nickname when it changes
+                        let old_key = PlayerProfile::nickname_key(&profile.nickname);
+                        if old_key != key {
+                            self.state
+                                .nicknames
+                                .remove(&old_key)
+                                .expect("Failed to release nickname");
+                        }
+                        profile.created_at
+                    }
+                    None => timestamp,
+                };
+
+                let profile = PlayerProfile {
+                    nickname,
+                    avatar,
+                    country,
+                    created_at,
+                };
+                self.state
+                    .nicknames
+                    .insert(&key, caller.clone())
+                    .expect("Failed to reserve nickname");
+                self.state
+                    .player_profiles
+                    .insert(&caller, profile)
+                    .expect("Failed to update profile");
+
+                1
+            }
+
+            Operation::ClearProfile => {
+                let Some(profile) = self
+                    .state
+                    .player_profiles
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get profile")
+                else {
+                    return 0;
+                };
+
+                let key = PlayerProfile::nickname_key(&profile.nickname);
+                self.state
+                    .nicknames
+                    .remove(&key)
+                    .expect("Failed to release nickname");
+                self.state
+                    .player_profiles
+                    .remove(&caller)
+                    .expect("Failed to remove profile");
+
+                1
+            }
+
+            Operation::AddFriend { player } => {
+                if player == caller {
+                    return 0;
+                }
+
+                let mut friends = self
+                    .state
+                    .friends
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get friends")
+                    .unwrap_or_default();
+                if friends.contains(&player) {
+                    return 0;
+                }
+
+                friends.push(player);
+                self.state
+                    .friends
+                    .insert(&caller, friends)
+                    .expect("Failed to update friends");
+
+                1
+            }
+
+            Operation::RemoveFriend { player } => {
+                let mut friends = self
+                    .state
+                    .friends
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get friends")
+                    .unwrap_or_default();
+                let Some(position) = friends.iter().position(|friend| *friend == player) else {
+                    return 0;
+                };
+
+                friends.remove(position);
+                if friends.is_empty() {
+                    self.state
+                        .friends
+                        .remove(&caller)
+                        .expect("Failed to update friends");
+                } else {
+                    self.state
+                        .friends
+                        .insert(&caller, friends)
+                        .expect("Failed to update friends");
+                }
+
+                1
+            }
+
+            Operation::Challenge {
+                opponent,
+                opponent_chain,
+                stake,
+                currency,
+                rules,
+                room_code,
+            } => {
+                if opponent == caller || !rules.is_valid() {
+                    return 0;
+                }
+
+                if self.is_blocked_pair(&opponent, &caller).await {
+                    return 0;
+                }
+
+                // Get next challenge ID
+                let id = *self.state.next_challenge_id.get();
+                self.state.next_challenge_id.set(id + 1);
+
+                let challenge = Challenge {
+                    id,
+                    challenger: caller.clone(),
+                    opponent: opponent.clone(),
+                    stake,
+                    currency,
+                    rules,
+                    room_code: room_code.clone(),
+                    status: ChallengeStatus::Pending,
+                    game_id: None,
+                    created_at: timestamp,
+                    responded_at: None,
+                };
+                self.state
+                    .challenges
+                    .insert(&id, challenge)
+                    .expect("Failed to insert challenge");
+
+                for player in [&caller, &opponent] {
+                    let mut pending = self
+                        .state
+                        .pending_challenges
+                        .get(player)
+                        .await
+                        .expect("Failed to get challenges")
+                        .unwrap_or_default();
+                    pending.push(id);
+                    self.state
+                        .pending_challenges
+                        .insert(player, pending)
+                        .expect("Failed to update challenges");
+                }
+
+                // Update stake pool (challenger's stake)
+                self.add_to_stake_pool(currency, stake).await;
+
+                if opponent_chain != self.runtime.chain_id() {
+                    self.runtime
+                        .prepare_message(Message::ChallengeIssued {
+                            challenge_id: id,
+                            challenger: caller,
+                            opponent,
+                            stake,
+                            currency,
+                            rules,
+                            room_code,
+                        })
+                        .send_to(opponent_chain);
+                }
+
+                id
+            }
+
+            Operation::AcceptChallenge { challenge_id } => {
+                let mut challenge = self
+                    .state
+                    .challenges
+                    .get(&challenge_id)
+                    .await
+                    .expect("Failed to get challenge")
+                    .expect("Challenge not found");
+
+                if challenge.status != ChallengeStatus::Pending || challenge.opponent != caller {
+                    return 0;
+                }
+
+                if self.is_blocked_pair(&challenge.challenger, &caller).await {
+                    return 0; // Blocked after the challenge was issued; decline instead
+                }
+
+                // Get next game ID
+                let game_id = *self.state.next_game_id.get();
+                self.state.next_game_id.set(game_id + 1);
+
+                let mut game = Game::new(
+                    game_id,
+                    challenge.challenger.clone(),
+                    challenge.stake,
+                    challenge.room_code.clone(),
+                    timestamp,
+                );
+                game.currency = challenge.currency;
+                game.rules = challenge.rules;
+                game.opponent = Some(caller);
+                game.status = GameStatus::Active;
+                game.started_at = Some(timestamp);
+                self.save_game(game).await;
+
+                // Update stake pool (opponent's stake)
+                self.add_to_stake_pool(challenge.currency, challenge.stake)
+                    .await;
+
+                challenge.status = ChallengeStatus::Accepted;
+                challenge.game_id = Some(game_id);
+                challenge.responded_at = Some(timestamp);
+                self.resolve_challenge(challenge).await;
+
+                game_id
+            }
+
+            Operation::DeclineChallenge { challenge_id } => {
+                let mut challenge = self
+                    .state
+                    .challenges
+                    .get(&challenge_id)
+                    .await
+                    .expect("Failed to get challenge")
+                    .expect("Challenge not found");
+
+                if challenge.status != ChallengeStatus::Pending || challenge.opponent != caller {
+                    return 0;
+                }
+
+                challenge.status = ChallengeStatus::Declined;
+                challenge.responded_at = Some(timestamp);
+                self.refund_challenge(challenge).await;
+
+                challenge_id
+            }
+
+            Operation::WithdrawChallenge { challenge_id } => {
+                let mut challenge = self
+                    .state
+                    .challenges
+                    .get(&challenge_id)
+                    .await
+                    .expect("Failed to get challenge")
+                    .expect("Challenge not found");
+
+                if challenge.status != ChallengeStatus::Pending || challenge.challenger != caller {
+                    return 0;
+                }
+
+                challenge.status = ChallengeStatus::Withdrawn;
+                challenge.responded_at = Some(timestamp);
+                self.refund_challenge(challenge).await;
+
+                challenge_id
+            }
+
+            Operation::BlockPlayer { player } => {
+                if player == caller {
+                    return 0;
+                }
+
+                let mut blocked = self
+                    .state
+                    .blocked_players
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get blocklist")
+                    .unwrap_or_default();
+                if blocked.contains(&player) {
+                    return 0;
+                }
+
+                blocked.push(player);
+                self.state
+                    .blocked_players
+                    .insert(&caller, blocked)
+                    .expect("Failed to update blocklist");
+
+                1
+            }
+
+            Operation::UnblockPlayer { player } => {
+                let mut blocked = self
+                    .state
+                    .blocked_players
+                    .get(&caller)
+                    .await
+                    .expect("Failed to get blocklist")
+                    .unwrap_or_default();
+                let Some(position) = blocked.iter().position(|b| *b == player) else {
+                    return 0;
+                };
+
+                blocked.remove(position);
+                if blocked.is_empty() {
+                    self.state
+                        .blocked_players
+                        .remove(&caller)
+                        .expect("Failed to update blocklist");
+                } else {
+                    self.state
+                        .blocked_players
+                        .insert(&caller, blocked)
+                        .expect("Failed to update blocklist");
+                }
+
+                1
+            }
+        }
+    }
+
+    async fn execute_message(&mut self, message: Self::Message) {
+        // Game notifications need no handling
+        let Message::ChallengeIssued {
+            challenge_id,
+            challenger,
+            opponent,
+            stake,
+            currency,
+            rules,
+            room_code,
+        } = message
+        else {
+            return;
+        };
+
+        let origin_chain = self
+            .runtime
+            .message_origin_chain_id()
+            .expect("Message without an origin chain");
+        let timestamp = self.runtime.system_time().micros();
+
+        let mut received = self
+            .state
+            .received_challenges
+            .get(&opponent)
+            .await
+            .expect("Failed to get challenges")
+            .unwrap_or_default();
+        received.push(ReceivedChallenge {
+            origin_chain,
+            challenge: Challenge {
+                id: challenge_id,
+                challenger,
+                opponent: opponent.clone(),
+                stake,
+                currency,
+                rules,
+                room_code,
+                status: ChallengeStatus::Pending,
+                game_id: None,
+                created_at: timestamp,
+                responded_at: None,
+            },
+        });
+        self.state
+            .received_challenges
+            .insert(&opponent, received)
+            .expect("Failed to update challenges");
+    }
+
+    async fn store(mut self) {
+        // Escrow accounting must balance after every block in debug and test builds
+        if cfg!(debug_assertions) {
+            let drift = self.state.stake_pool_drift().await;
+            assert!(
+                drift.is_empty(),
+                "Stake pools drifted from escrow: {drift:?}"
+            );
+        }
+
+        self.state.save().await.expect("Failed to save state");
+    }
+}
+
+impl AirHockeyContract {
+    /// Bring register-level state up to `SCHEMA_VERSION`; map records
+    /// upgrade when read or through `Operation::MigrateRecords`
+    fn upgrade_schema(&mut self) {
+        let version = *self.state.schema_version.get();
+        if version >= SCHEMA_VERSION {
+            return;
+        }
+
+        // Register migrations go here, one step per version; the record
+        // migration restarts from the first game
+        if version == 0 {
+            // State from before versioning has none of the later counters
+            self.state.archive_cursor.set(1);
+            self.state.next_series_id.set(1);
+            self.state.next_tournament_id.set(1);
+            self.state.next_season_id.set(1);
+            self.state.next_quest_id.set(1);
+            self.state.next_challenge_id.set(1);
+
+            // The first layout kept a single native pool
+            let pool = *self.state.legacy_total_stake_pool.get();
+            if pool > 0 {
+                self.state
+                    .stake_pools
+                    .insert(&StakeCurrency::Native, Amount::from_attos(pool.into()))
+                    .expect("Failed to update stake pool");
+            }
+            self.state.legacy_total_stake_pool.set(0);
+        }
+        self.state.migration_cursor.set(1);
+        self.state.schema_version.set(SCHEMA_VERSION);
+    }
+
+    /// Store a game, moving it between the status indexes when its status changed
+    async fn save_game(&mut self, game: Game) {
+        let previous = self
+            .state
+            .load_game(game.id)
+            .await
+            .map(|stored| stored.status);
+        let id = game.id;
+        if previous != Some(game.status) {
+            self.index_game_status(id, previous, game.status);
+        }
+        self.state
+            .games
+            .insert(&id, game.into())
+            .expect("Failed to update game");
+    }
+
+    /// Move a game ID from the index of its previous status to that of its new one
+    fn index_game_status(
+        &mut self,
+        game_id: u64,
+        previous: Option<GameStatus>,
+        status: GameStatus,
+    ) {
+        match previous {
+            Some(GameStatus::Waiting) => self
+                .state
+                .waiting_games
+                .remove(&game_id)
+                .expect("Failed to update index"),
+            Some(GameStatus::Active) => self
+                .state
+                .active_games
+                .remove(&game_id)
+                .expect("Failed to update index"),
+            Some(GameStatus::Disputed) => self
+                .state
+                .disputed_games
+                .remove(&game_id)
+                .expect("Failed to update index"),
+            _ => {}
+        }
+
+        match status {
+            GameStatus::Waiting => self
+                .state
+                .waiting_games
+                .insert(&game_id)
+                .expect("Failed to update index"),
+            GameStatus::Active => self
+                .state
+                .active_games
+                .insert(&game_id)
+                .expect("Failed to update index"),
+            GameStatus::Completed => {
+                let recent = self.state.recent_completed_games.get_mut();
+                if !recent.contains(&game_id) {
+                    recent.push(game_id);
+                    if recent.len() > RECENT_COMPLETED_GAMES {
+                        recent.remove(0);
+                    }
+                }
+            }
+            GameStatus::Disputed => self
+                .state
+                .disputed_games
+                .insert(&game_id)
+                .expect("Failed to update index"),
+            GameStatus::Cancelled => {}
+        }
+    }
+
+    /// Walk up to `max_games` game IDs from the scan cursor, archiving finished
+    /// games past the retention window and skipping those still in play or
+    /// too recent; the scan wraps back to the oldest unarchived game once it
+    /// passes the newest
+    async fn archive_games(&mut self, max_games: usize, hash_history: bool, timestamp: u64) {
+        let next_game_id = *self.state.next_game_id.get();
+        let mut oldest = *self.state.archive_cursor.get();
+        let mut cursor = (*self.state.archive_scan_cursor.get()).max(oldest);
+        let mut walked = 0;
+
+        while walked < max_games && cursor < next_game_id {
+            walked += 1;
+            let Some(game) = self.state.load_game(cursor).await else {
+                cursor += 1; // Already gone
+                continue;
+            };
+
+            let finished = matches!(game.status, GameStatus::Completed | GameStatus::Cancelled);
+            let expired = game.ended_at.is_some_and(|ended_at| {
+                ended_at.saturating_add(ARCHIVE_RETENTION_MICROS) <= timestamp
+            });
+            if !finished || !expired {
+                cursor += 1; // Checked again on the next pass
+                continue;
+            }
+
+            for player in std::iter::once(&game.creator).chain(game.opponent.as_ref()) {
+                let mut archive = self
+                    .state
+                    .player_archives
+                    .get(player)
+                    .await
+                    .expect("Failed to get archive")
+                    .unwrap_or_default();
+                archive.record(player, &game, hash_history);
+                self.state
+                    .player_archives
+                    .insert(player, archive)
+                    .expect("Failed to update archive");
+            }
+
+            self.state
+                .games
+                .remove(&cursor)
+                .expect("Failed to remove game");
+            self.state
+                .legacy_games
+                .remove(&cursor)
+                .expect("Failed to remove game");
+            self.state
+                .goal_timelines
+                .remove(&cursor)
+                .expect("Failed to remove timeline");
+            self.state
+                .side_pools
+                .remove(&cursor)
+                .expect("Failed to remove side pool");
+            self.state
+                .game_sponsorships
+                .remove(&cursor)
+                .expect("Failed to remove sponsorship");
+            self.state
+                .recent_completed_games
+                .get_mut()
+                .retain(|id| *id != cursor);
+
+            cursor += 1;
+        }
+
+        // Move the oldest cursor past games already archived
+        while oldest < cursor && self.state.load_game(oldest).await.is_none() {
+            oldest += 1;
+        }
+        if cursor >= next_game_id {
+            cursor = oldest;
+        }
+
+        self.state.archive_cursor.set(oldest);
+        self.state.archive_scan_cursor.set(cursor);
+    }
+
+    /// Rewrite up to `max_records` outdated or legacy games and player stats in the
+    /// current layout, indexing every waiting, active or disputed game walked
+    async fn migrate_records(&mut self, max_records: usize) {
+        let mut budget = max_records;
+
+        // Games are walked in ID order from the cursor
+        let next_game_id = *self.state.next_game_id.get();
+        let mut cursor = *self.state.migration_cursor.get();
+        while budget > 0 && cursor < next_game_id {
+            let stored = match self
+                .state
+                .games
+                .get(&cursor)
+                .await
+                .expect("Failed to get game")
+            {
+                Some(record) => Some((record.is_latest(), Game::from(record))),
+                None => self
+                    .state
+                    .legacy_games
+                    .get(&cursor)
+                    .await
+                    .expect("Failed to get game")
+                    .map(|game| (false, Game::from(game))),
+            };
+            if let Some((latest, game)) = stored {
+                // Only the in-play indexes are rebuilt; the recent list keeps
+                // completion order and is not backfilled
+                if matches!(
+                    game.status,
+                    GameStatus::Waiting | GameStatus::Active | GameStatus::Disputed
+                ) {
+                    self.index_game_status(game.id, None, game.status);
+                }
+                if !latest {
+                    self.state
+                        .games
+                        .insert(&cursor, game.into())
+                        .expect("Failed to update game");
+                    self.state
+                        .legacy_games
+                        .remove(&cursor)
+                        .expect("Failed to remove game");
+                }
+            }
+            cursor += 1;
+            budget -= 1;
+        }
+        self.state.migration_cursor.set(cursor);
+
+        // Stats are few enough to scan; migrated entries drop out of the scan.
+        // Legacy entries move first, unless a newer record already shadows
+        // them; lifetime stats go through the read path to seed their streaks
+        let mut legacy = Vec::new();
+        self.state
+            .legacy_player_stats
+            .for_each_index_while(|player| {
+                if legacy.len() >= budget {
+                    return Ok(false);
+                }
+                legacy.push(player);
+                Ok(true)
+            })
+            .await
+            .expect("Failed to scan stats");
+        budget -= legacy.len();
+        for player in legacy {
+            let stats = self
+                .state
+                .load_player_stats(&player)
+                .await
+                .expect("Stats not found");
+            self.state
+                .player_stats
+                .insert(&player, stats.into())
+                .expect("Failed to update stats");
+            self.state
+                .legacy_player_stats
+                .remove(&player)
+                .expect("Failed to remove stats");
+        }
+
+        let mut legacy = Vec::new();
+        self.state
+            .legacy_season_stats
+            .for_each_index_value_while(|key, stats| {
+                if legacy.len() >= budget {
+                    return Ok(false);
+                }
+                legacy.push((key, stats.into_owned()));
+                Ok(true)
+            })
+            .await
+            .expect("Failed to scan stats");
+        budget -= legacy.len();
+        for (key, stats) in legacy {
+            if !self
+                .state
+                .season_stats
+                .contains_key(&key)
+                .await
+                .expect("Failed to get stats")
+            {
+                self.state
+                    .season_stats
+                    .insert(&key, PlayerStats::from(stats).into())
+                    .expect("Failed to update stats");
+            }
+            self.state
+                .legacy_season_stats
+                .remove(&key)
+                .expect("Failed to remove stats");
+        }
+
+        let mut outdated = Vec::new();
+        self.state
+            .player_stats
+            .for_each_index_value_while(|player, record| {
+                if outdated.len() >= budget {
+                    return Ok(false);
+                }
+                if !record.is_latest() {
+                    outdated.push(player);
+                }
+                Ok(true)
+            })
+            .await
+            .expect("Failed to scan stats");
+        budget -= outdated.len();
+        for player in outdated {
+            let stats = self
+                .state
+                .load_player_stats(&player)
+                .await
+                .expect("Stats not found");
+            self.state
+                .player_stats
+                .insert(&player, stats.into())
+                .expect("Failed to update stats");
+        }
+
+        let mut outdated = Vec::new();
+        self.state
+            .season_stats
+            .for_each_index_value_while(|key, record| {
+                if outdated.len() >= budget {
+                    return Ok(false);
+                }
+                if !record.is_latest() {
+                    outdated.push((key, record.into_owned()));
+                }
+                Ok(true)
+            })
+            .await
+            .expect("Failed to scan stats");
+        for (key, record) in outdated {
+            self.state
+                .season_stats
+                .insert(&key, PlayerStats::from(record).into())
+                .expect("Failed to update stats");
+        }
+    }
+
+    /// Check if either player has blocked the other
+    async fn is_blocked_pair(&self, player1: &str, player2: &str) -> bool {
+        for (blocker, blocked) in [(player1, player2), (player2, player1)] {
+            let list = self
+                .state
+                .blocked_players
+                .get(blocker)
+                .await
+                .expect("Failed to get blocklist")
+                .unwrap_or_default();
+            if list.iter().any(|player| player == blocked) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Return the challenger's escrowed stake and close the challenge
+    async fn refund_challenge(&mut self, challenge: Challenge) {
+        // Update stake pool
+        self.remove_from_stake_pool(challenge.currency, challenge.stake)
+            .await;
+
+        self.resolve_challenge(challenge).await;
+    }
+
+    /// Store an answered challenge and drop it from both players' pending lists
+    async fn resolve_challenge(&mut self, challenge: Challenge) {
+        let challenge_id = challenge.id;
+        for player in [&challenge.challenger, &challenge.opponent] {
+            let mut pending = self
+                .state
+                .pending_challenges
+                .get(player)
+                .await
+                .expect("Failed to get challenges")
+                .unwrap_or_default();
+            pending.retain(|id| *id != challenge_id);
+            if pending.is_empty() {
+                self.state
+                    .pending_challenges
+                    .remove(player)
+                    .expect("Failed to update challenges");
+            } else {
+                self.state
+                    .pending_challenges
+                    .insert(player, pending)
+                    .expect("Failed to update challenges");
+            }
+        }
+
+        self.state
+            .challenges
+            .insert(&challenge_id, challenge)
+            .expect("Failed to update challenge");
+    }
+
+    /// Settle a game with the score its players agreed on, or that nobody
+    /// disputed in time
+    async fn settle_pending_result(
+        &mut self,
+        mut game: Game,
+        pending: PendingResult,
+        timestamp: u64,
+    ) {
+        if let Some(timeline) = pending.timeline {
+            self.state
+                .goal_timelines
+                .insert(&game.id, timeline)
+                .expect("Failed to store timeline");
+        }
+        game.replay_hash = pending.replay_hash;
+
+        self.settle_game(
+            game,
+            pending.player1_score,
+            pending.player2_score,
+            timestamp,
+        )
+        .await;
+    }
+
+    /// Move an active game into dispute, dropping any pending result
+    async fn open_dispute(&mut self, mut game: Game, disputer: String, timestamp: u64) {
+        self.state
+            .pending_results
+            .remove(&game.id)
+            .expect("Failed to remove pending result");
+
+        game.status = GameStatus::Disputed;
+        game.disputed_by = Some(disputer);
+        game.disputed_at = Some(timestamp);
+
+        self.save_game(game).await;
+    }
+
+    /// Record the final score of an active or disputed game and pay out its stakes
+    async fn settle_game(
+        &mut self,
+        mut game: Game,
+        player1_score: u8,
+        player2_score: u8,
+        timestamp: u64,
+    ) {
+        self.state
+            .pending_results
+            .remove(&game.id)
+            .expect("Failed to remove pending result");
+        game.player1_score = player1_score;
+        game.player2_score = player2_score;
+        game.status = GameStatus::Completed;
+        game.ended_at = Some(timestamp);
+
+        // Determine winner
+        game.winner = if player1_score > player2_score {
+            Some(game.creator.clone())
+        } else if player2_score > player1_score {
+            game.opponent.clone()
+        } else {
+            None // Draw
+        };
+
+        // Update player stats
+        self.update_player_stats(&game, timestamp).await;
+
+        // Update stake pool (remove both stakes)
+        self.remove_from_stake_pool(game.currency, game.total_pot())
+            .await;
+        self.close_side_pool(
+            game.id,
+            Some(BetOutcome::from_score(player1_score, player2_score)),
+            timestamp,
+        )
+        .await;
+        self.close_game_sponsorship(game.id, game.winner.clone(), timestamp)
+            .await;
+
+        self.save_game(game.clone()).await;
+
+        // Series games carry no stake of their own; the series settles instead
+        if let Some(series_id) = game.series_id {
+            self.advance_series(series_id, &game, timestamp).await;
+        }
+        if let Some(tournament_id) = game.tournament_id {
+            self.advance_tournament(tournament_id, &game, timestamp)
+                .await;
+        }
+    }
+
+    /// Refund a game that cannot be settled; series and tournaments replay the pairing
+    async fn void_game(&mut self, mut game: Game, timestamp: u64) {
+        self.state
+            .pending_results
+            .remove(&game.id)
+            .expect("Failed to remove pending result");
+        game.status = GameStatus::Cancelled;
+        game.ended_at = Some(timestamp);
+
+        // Update stake pool (refund both stakes)
+        self.remove_from_stake_pool(game.currency, game.total_pot())
+            .await;
+        self.close_side_pool(game.id, None, timestamp).await;
+        self.close_game_sponsorship(game.id, None, timestamp).await;
+
+        self.save_game(game.clone()).await;
+
+        if let Some(series_id) = game.series_id {
+            let mut series = self
+                .state
+                .series
+                .get(&series_id)
+                .await
+                .expect("Failed to get series")
+                .expect("Series not found");
+            self.start_series_game(&mut series, timestamp).await;
+            self.state
+                .series
+                .insert(&series_id, series)
+                .expect("Failed to update series");
+        }
+
+        if let Some(tournament_id) = game.tournament_id {
+            let mut tournament = self
+                .state
+                .tournaments
+                .get(&tournament_id)
+                .await
+                .expect("Failed to get tournament")
+                .expect("Tournament not found");
+            if let (Some((round, index)), Some(opponent)) =
+                (tournament.find_match(game.id), game.opponent.clone())
+            {
+                let rematch = self
+                    .create_tournament_game(&tournament, game.creator.clone(), opponent, timestamp)
+                    .await;
+                tournament.rounds[round][index].game_id = Some(rematch);
+            }
+            self.state
+                .tournaments
+                .insert(&tournament_id, tournament)
+                .expect("Failed to update tournament");
+        }
+    }
+
+    /// Pay out a game's side pool on its final outcome, or refund every bet
+    /// when the game has none
+    async fn close_side_pool(&mut self, game_id: u64, outcome: Option<BetOutcome>, timestamp: u64) {
+        let Some(mut pool) = self
+            .state
+            .side_pools
+            .get(&game_id)
+            .await
+            .expect("Failed to get side pool")
+        else {
+            return;
+        };
+
+        match outcome {
+            Some(outcome) => pool.settle(outcome, timestamp),
+            None => pool.refund(timestamp),
+        }
+
+        // Update stake pool (every bet leaves escrow)
+        self.remove_from_stake_pool(pool.currency, pool.total())
+            .await;
+        self.state
+            .side_pools
+            .insert(&game_id, pool)
+            .expect("Failed to update side pool");
+    }
+
+    /// Pay a game's sponsor bonus to its winner, or refund it when there is none
+    async fn close_game_sponsorship(
+        &mut self,
+        game_id: u64,
+        winner: Option<String>,
+        timestamp: u64,
+    ) {
+        let Some(mut sponsorship) = self
+            .state
+            .game_sponsorships
+            .get(&game_id)
+            .await
+            .expect("Failed to get sponsorship")
+        else {
+            return;
+        };
+
+        sponsorship.close(winner, timestamp);
+
+        // Update stake pool (the bonus leaves escrow)
+        self.remove_from_stake_pool(sponsorship.currency, sponsorship.total())
+            .await;
+        self.state
+            .game_sponsorships
+            .insert(&game_id, sponsorship)
+            .expect("Failed to update sponsorship");
+    }
+
+    /// Pay a tournament's sponsor bonus to its champion, or refund it when there is none
+    async fn close_tournament_sponsorship(
+        &mut self,
+        tournament_id: u64,
+        champion: Option<String>,
+        timestamp: u64,
+    ) {
+        let Some(mut sponsorship) = self
+            .state
+            .tournament_sponsorships
+            .get(&tournament_id)
+            .await
+            .expect("Failed to get sponsorship")
+        else {
+            return;
+        };
+
+        sponsorship.close(champion, timestamp);
+
+        // Update stake pool (the bonus leaves escrow)
+        self.remove_from_stake_pool(sponsorship.currency, sponsorship.total())
+            .await;
+        self.state
+            .tournament_sponsorships
+            .insert(&tournament_id, sponsorship)
+            .expect("Failed to update sponsorship");
+    }
+
+    /// Settle a game as a win for the other side and record the forfeit
+    async fn forfeit_game(&mut self, game: Game, loser_index: usize, timestamp: u64) {
+        let loser = if loser_index == 0 {
+            game.creator.clone()
+        } else {
+            game.opponent.clone().expect("Game has no opponent")
+        };
+
+        let target = game.rules.points_to_win;
+        if loser_index == 0 {
+            self.settle_game(game, 0, target, timestamp).await;
+        } else {
+            self.settle_game(game, target, 0, timestamp).await;
+        }
+        self.update_lifetime_stats(&loser, |stats| stats.forfeits += 1)
+            .await;
+    }
+
+    /// Check if the commit-reveal deadline of a disputed game has passed
+    fn dispute_expired(game: &Game, timestamp: u64) -> bool {
+        game.dispute_deadline()
+            .is_some_and(|deadline| timestamp > deadline)
+    }
+
+    /// Number of ticks a disputed game is replayed for
+    fn replay_ticks(game: &Game) -> u32 {
+        game.rules
+            .time_limit_secs
+            .map_or(MAX_REPLAY_TICKS, |secs| {
+                secs.saturating_mul(PHYSICS_CONFIG.game.tick_rate)
+            })
+            .min(MAX_REPLAY_TICKS)
+    }
+
+    /// Replay a disputed game once both input logs are revealed and settle its score
+    async fn replay_dispute(&mut self, mut game: Game, timestamp: u64) {
+        let mut frames: Vec<InputFrame> = Vec::new();
+        for index in 0..2u8 {
+            match self
+                .state
+                .revealed_inputs
+                .get(&(game.id, index))
+                .await
+                .expect("Failed to get inputs")
+            {
+                Some(log) => frames.extend(log),
+                None => return, // Still waiting for the other player
+            }
+        }
+        self.state
+            .revealed_inputs
+            .remove(&(game.id, 0))
+            .expect("Failed to remove inputs");
+        self.state
+            .revealed_inputs
+            .remove(&(game.id, 1))
+            .expect("Failed to remove inputs");
+
+        // Stable sort keeps each player's frames in submission order
+        frames.sort_by_key(|frame| frame.tick);
+
+        let mut config = PHYSICS_CONFIG;
+        config.game.max_score = game.rules.points_to_win;
+        let outcome = replay(config, &frames, Self::replay_ticks(&game));
+
+        let settled = game
+            .rules
+            .is_possible_score(outcome.player1_score, outcome.player2_score);
+        game.replay_verdict = Some(ReplayVerdict {
+            player1_score: outcome.player1_score,
+            player2_score: outcome.player2_score,
+            ticks: outcome.ticks,
+            settled,
+        });
+
+        if settled {
+            // The replay knows exactly when each goal went in
+            let tick_rate = u64::from(config.game.tick_rate);
+            let timeline: Vec<GoalEvent> = outcome
+                .goals
+                .iter()
+                .map(|goal| GoalEvent {
+                    scorer: if goal.scorer == Player::One { 1 } else { 2 },
+                    offset_micros: u64::from(goal.tick) * 1_000_000 / tick_rate,
+                })
+                .collect();
+            self.state
+                .goal_timelines
+                .insert(&game.id, timeline)
+                .expect("Failed to store timeline");
+
+            // A dispute is lost when the replay hands the win to the other player
+            let winner = match outcome.player1_score.cmp(&outcome.player2_score) {
+                std::cmp::Ordering::Greater => Some(game.creator.clone()),
+                std::cmp::Ordering::Less => game.opponent.clone(),
+                std::cmp::Ordering::Equal => None,
+            };
+            let lost_by = game
+                .disputed_by
+                .clone()
+                .filter(|disputer| winner.as_ref().is_some_and(|w| w != disputer));
+
+            self.settle_game(
+                game,
+                outcome.player1_score,
+                outcome.player2_score,
+                timestamp,
+            )
+            .await;
+
+            if let Some(disputer) = lost_by {
+                self.update_lifetime_stats(&disputer, |stats| stats.lost_disputes += 1)
+                    .await;
+            }
+        } else {
+            self.void_game(game, timestamp).await;
+        }
+    }
+
+    /// Update player statistics after a game
+    async fn update_player_stats(&mut self, game: &Game, timestamp: u64) {
+        let opponent = match game.opponent.as_ref() {
+            Some(o) => o.clone(),
+            None => return,
+        };
+
+        let key = pair_key(&game.creator, &opponent);
+        // Only native stakes count toward token flow; other currencies are
+        // not comparable
+        let native_stake = match game.currency {
+            StakeCurrency::Native => game.stake,
+            StakeCurrency::Token(_) => Amount::ZERO,
+        };
+        let winner = game
+            .winner
+            .as_ref()
+            .map(|winner| if *winner == key.0 { 0 } else { 1 });
+
+        let mut head_to_head = self
+            .state
+            .head_to_head
+            .get(&key)
+            .await
+            .expect("Failed to get head-to-head")
+            .unwrap_or_default();
+        head_to_head.record(game, winner, native_stake);
+        self.state
+            .head_to_head
+            .insert(&key, head_to_head)
+            .expect("Failed to update head-to-head");
+
+        let ranked = self
+            .record_pair_activity(key, winner, native_stake, timestamp)
+            .await;
+
+        // Update lifetime stats
+        let mut creator_stats = self
+            .state
+            .load_player_stats(&game.creator)
+            .await
+            .unwrap_or_default();
+        let mut opponent_stats = self
+            .state
+            .load_player_stats(&opponent)
+            .await
+            .unwrap_or_default();
+
+        PlayerStats::record_game(&mut creator_stats, &mut opponent_stats, game, ranked);
+
+        self.award_game_badges(&game.creator, game, &creator_stats, ranked, timestamp)
+            .await;
+        self.award_game_badges(&opponent, game, &opponent_stats, ranked, timestamp)
+            .await;
+
+        self.state
+            .player_stats
+            .insert(&game.creator, creator_stats.into())
+            .expect("Failed to update stats");
+        self.state
+            .player_stats
+            .insert(&opponent, opponent_stats.into())
+            .expect("Failed to update stats");
+
+        if ranked {
+            self.record_quest_progress(game, &opponent, timestamp).await;
+        }
+
+        // Update stats for the current season
+        let Some(season_id) = *self.state.current_season.get() else {
+            return;
+        };
+        let mut season = self
+            .state
+            .seasons
+            .get(&season_id)
+            .await
+            .expect("Failed to get season")
+            .expect("Season not found");
+
+        let creator_key = (season_id, game.creator.clone());
+        let opponent_key = (season_id, opponent.clone());
+        let mut creator_stats = self
+            .state
+            .load_season_stats(&creator_key)
+            .await
+            .unwrap_or_default();
+        let mut opponent_stats = self
+            .state
+            .load_season_stats(&opponent_key)
+            .await
+            .unwrap_or_default();
+
+        PlayerStats::record_game(&mut creator_stats, &mut opponent_stats, game, ranked);
+
+        self.state
+            .season_stats
+            .insert(&creator_key, creator_stats.into())
+            .expect("Failed to update stats");
+        self.state
+            .season_stats
+            .insert(&opponent_key, opponent_stats.into())
+            .expect("Failed to update stats");
+
+        for player in [&game.creator, &opponent] {
+            if !season.players.contains(player) {
+                season.players.push(player.clone());
+            }
+        }
+        self.state
+            .seasons
+            .insert(&season_id, season)
+            .expect("Failed to update season");
+    }
+
+    /// Count a game toward a pair's collusion window, flagging the pair
+    /// when it goes over the limits; returns whether the game is ranked
+    async fn record_pair_activity(
+        &mut self,
+        key: (String, String),
+        winner: Option<usize>,
+        native_stake: Amount,
+        timestamp: u64,
+    ) -> bool {
+        let mut activity = self
+            .state
+            .pair_activity
+            .get(&key)
+            .await
+            .expect("Failed to get pair activity")
+            .unwrap_or_default();
+
+        let limits = *self.state.collusion_limits.get();
+        let ranked = activity.record(winner, native_stake, timestamp, &limits);
+        if !ranked {
+            self.state
+                .flagged_pairs
+                .insert(&key, timestamp)
+                .expect("Failed to flag pair");
+        }
+        self.state
+            .pair_activity
+            .insert(&key, activity)
+            .expect("Failed to update pair activity");
+
+        ranked
+    }
+
+    /// Count stake won outside a game toward a pair's collusion window,
+    /// flagging the pair when it goes over the limits; returns whether the
+    /// pair is still within them
+    async fn record_pair_tokens(
+        &mut self,
+        key: (String, String),
+        winner: usize,
+        native_stake: Amount,
+        timestamp: u64,
+    ) -> bool {
+        let mut activity = self
+            .state
+            .pair_activity
+            .get(&key)
+            .await
+            .expect("Failed to get pair activity")
+            .unwrap_or_default();
+
+        let limits = *self.state.collusion_limits.get();
+        let within_limits = activity.record_tokens(Some(winner), native_stake, timestamp, &limits);
+        if !within_limits {
+            self.state
+                .flagged_pairs
+                .insert(&key, timestamp)
+                .expect("Failed to flag pair");
+        }
+        self.state
+            .pair_activity
+            .insert(&key, activity)
+            .expect("Failed to update pair activity");
+
+        within_limits
+    }
+
+    /// Add escrowed tokens to the stake pool of their currency
+    async fn add_to_stake_pool(&mut self, currency: StakeCurrency, amount: Amount) {
+        let current_pool = self
+            .state
+            .stake_pools
+            .get(&currency)
+            .await
+            .expect("Failed to get stake pool")
+            .unwrap_or_default();
+        let pool = current_pool.try_add(amount).expect("Stake pool overflow");
+        self.state
+            .stake_pools
+            .insert(&currency, pool)
+            .expect("Failed to update stake pool");
+    }
+
+    /// Release escrowed tokens from the stake pool of their currency
+    async fn remove_from_stake_pool(&mut self, currency: StakeCurrency, amount: Amount) {
+        let current_pool = self
+            .state
+            .stake_pools
+            .get(&currency)
+            .await
+            .expect("Failed to get stake pool")
+            .unwrap_or_default();
+        let pool = current_pool.try_sub(amount).expect("Stake pool underflow");
+        self.state
+            .stake_pools
+            .insert(&currency, pool)
+            .expect("Failed to update stake pool");
+    }
+
+    /// Apply a change to a player's lifetime stats
+    async fn update_lifetime_stats(&mut self, player: &str, update: impl FnOnce(&mut PlayerStats)) {
+        let player = player.to_string();
+        let mut stats = self
+            .state
+            .load_player_stats(&player)
+            .await
+            .unwrap_or_default();
+        update(&mut stats);
+        self.state
+            .player_stats
+            .insert(&player, stats.into())
+            .expect("Failed to update stats");
+    }
+
+    /// Update a player's badges
+    async fn update_badges(&mut self, player: &str, update: impl FnOnce(&mut PlayerBadges)) {
+        let player = player.to_string();
+        let mut badges = self
+            .state
+            .player_badges
+            .get(&player)
+            .await
+            .expect("Failed to get badges")
+            .unwrap_or_default();
+        update(&mut badges);
+        self.state
+            .player_badges
+            .insert(&player, badges)
+            .expect("Failed to update badges");
+    }
+
+    /// Award the badges a settled game earns one of its players, given their
+    /// updated lifetime stats; only ranked games count toward wins and streaks
+    async fn award_game_badges(
+        &mut self,
+        player: &str,
+        game: &Game,
+        stats: &PlayerStats,
+        ranked: bool,
+        timestamp: u64,
+    ) {
+        let won = game.winner.as_deref() == Some(player);
+        let conceded = if player == game.creator {
+            game.player2_score
+        } else {
+            game.player1_score
+        };
+        let badge = |achievement| Badge {
+            achievement,
+            earned_at: timestamp,
+            game_id: Some(game.id),
+            tournament_id: None,
+        };
+
+        self.update_badges(player, |badges| {
+            if stats.games_played >= CENTURION_BADGE_GAMES {
+                badges.award(badge(Achievement::Centurion));
+            }
+
+            if !ranked || !won {
+                return;
+            }
+
+            badges.award(badge(Achievement::FirstWin));
+            if stats.current_streak >= WIN_STREAK_BADGE {
+                badges.award(badge(Achievement::WinStreak));
+            }
+            if conceded == 0 {
+                badges.award(badge(Achievement::Shutout));
+            }
+        })
+        .await;
+    }
+
+    /// Add token winnings and losses to a player's lifetime and current season stats
+    async fn credit_tokens(
+        &mut self,
+        player: &str,
+        currency: StakeCurrency,
+        won: Amount,
+        lost: Amount,
+    ) {
+        let player = player.to_string();
+        let mut stats = self
+            .state
+            .load_player_stats(&player)
+            .await
+            .unwrap_or_default();
+        stats.credit_tokens(currency, won, lost);
+        self.state
+            .player_stats
+            .insert(&player, stats.into())
+            .expect("Failed to update stats");
+
+        if let Some(season_id) = *self.state.current_season.get() {
+            let key = (season_id, player);
+            let mut stats = self.state.load_season_stats(&key).await.unwrap_or_default();
+            stats.credit_tokens(currency, won, lost);
+            self.state
+                .season_stats
+                .insert(&key, stats.into())
+                .expect("Failed to update stats");
+        }
+    }
+
+    /// Update the season and quest rewards a player received
+    async fn update_reward_totals(&mut self, player: &str, update: impl FnOnce(&mut RewardTotals)) {
+        let player = player.to_string();
+        let mut totals = self
+            .state
+            .reward_totals
+            .get(&player)
+            .await
+            .expect("Failed to get rewards")
+            .unwrap_or_default();
+        update(&mut totals);
+        self.state
+            .reward_totals
+            .insert(&player, totals)
+            .expect("Failed to update rewards");
+    }
+
+    /// Create the next game of an active series between the same two players
+    async fn start_series_game(&mut self, series: &mut Series, timestamp: u64) {
+        let id = *self.state.next_game_id.get();
+        self.state.next_game_id.set(id + 1);
+
+        let mut game = Game::new(
+            id,
+            series.creator.clone(),
+            Amount::ZERO,
+            series.room_code.clone(),
+            timestamp,
+        );
+        game.currency = series.currency;
+        game.opponent = series.opponent.clone();
+        game.status = GameStatus::Active;
+        game.started_at = Some(timestamp);
+        game.series_id = Some(series.id);
+
+        self.save_game(game).await;
+        series.game_ids.push(id);
+    }
+
+    /// Record a completed series game, then either settle the series or start the next game
+    async fn advance_series(&mut self, series_id: u64, game: &Game, timestamp: u64) {
+        let mut series = self
+            .state
+            .series
+            .get(&series_id)
+            .await
+            .expect("Failed to get series")
+            .expect("Series not found");
+
+        if game.player1_score > game.player2_score {
+            series.player1_wins += 1;
+        } else if game.player2_score > game.player1_score {
+            series.player2_wins += 1;
+        }
+
+        let wins_needed = series.wins_needed();
+        series.winner = if series.player1_wins >= wins_needed {
+            Some(series.creator.clone())
+        } else if series.player2_wins >= wins_needed {
+            series.opponent.clone()
+        } else {
+            None
+        };
+
+        if series.winner.is_some() {
+            series.status = GameStatus::Completed;
+            series.ended_at = Some(timestamp);
+
+            self.update_series_tokens(&series, timestamp).await;
+
+            // Update stake pool (remove both stakes)
+            self.remove_from_stake_pool(series.currency, series.total_pot())
+                .await;
+        } else {
+            self.start_series_game(&mut series, timestamp).await;
+        }
+
+        self.state
+            .series
+            .insert(&series_id, series)
+            .expect("Failed to update series");
+    }
+
+    /// Credit the series stake to the winner and debit it from the loser,
+    /// unless the stake takes the pair over the collusion limits
+    async fn update_series_tokens(&mut self, series: &Series, timestamp: u64) {
+        let (Some(winner), Some(opponent)) = (series.winner.as_ref(), series.opponent.as_ref())
+        else {
+            return;
+        };
+        let loser = if winner == &series.creator {
+            opponent
+        } else {
+            &series.creator
+        };
+
+        // Series games are staked at zero, so the series stake only reaches
+        // the pair's window here
+        let key = pair_key(winner, loser);
+        let native_stake = match series.currency {
+            StakeCurrency::Native => series.stake,
+            StakeCurrency::Token(_) => Amount::ZERO,
+        };
+        let slot = if *winner == key.0 { 0 } else { 1 };
+        if !self
+            .record_pair_tokens(key, slot, native_stake, timestamp)
+            .await
+        {
+            return;
+        }
+
+        self.credit_tokens(winner, series.currency, series.stake, Amount::ZERO)
+            .await;
+        self.credit_tokens(loser, series.currency, Amount::ZERO, series.stake)
+            .await;
+    }
+
+    /// Seed the bracket by rating and start the first round
+    async fn start_tournament(&mut self, tournament: &mut Tournament, timestamp: u64) {
+        // Highest rating first; registration order breaks ties
+        let mut seeded = Vec::with_capacity(tournament.players.len());
+        for player in &tournament.players {
+            let rating = self
+                .state
+                .load_player_stats(player)
+                .await
+                .unwrap_or_default()
+                .rating;
+            seeded.push((player.clone(), rating));
+        }
+        seeded.sort_by_key(|(_, rating)| std::cmp::Reverse(*rating));
+
+        let slots = Tournament::seed_order(seeded.len());
+        let mut first_round = Vec::with_capacity(slots.len() / 2);
+        let mut walkovers = Vec::new();
+        for pair in slots.chunks(2) {
+            let player1 = seeded[pair[0] - 1].0.clone();
+            let player2 = seeded[pair[1] - 1].0.clone();
+            let walkover = self.walkover_winner(&player1, &player2).await;
+            let game_id = match walkover {
+                Some(_) => {
+                    walkovers.push(first_round.len());
+                    None
+                }
+                None => Some(
+                    self.create_tournament_game(
+                        tournament,
+                        player1.clone(),
+                        player2.clone(),
+                        timestamp,
+                    )
+                    .await,
+                ),
+            };
+            first_round.push(BracketMatch {
+                player1: Some(player1),
+                player2: Some(player2),
+                game_id,
+                winner: walkover,
+            });
+        }
+
+        tournament.rounds = vec![first_round];
+        let mut matches = slots.len() / 4;
+        while matches > 0 {
+            tournament
+                .rounds
+                .push(vec![BracketMatch::default(); matches]);
+            matches /= 2;
+        }
+
+        tournament.status = GameStatus::Active;
+        tournament.started_at = Some(timestamp);
+
+        for index in walkovers {
+            self.advance_bracket(tournament, 0, index, timestamp).await;
+        }
+    }
+
+    /// Winner of a pairing that cannot be played because one player blocked
+    /// the other: the player who blocked concedes, and the upper slot
+    /// advances when both did
+    async fn walkover_winner(&self, player1: &str, player2: &str) -> Option<String> {
+        let mut blocked = [false; 2];
+        for (index, (blocker, other)) in [(player1, player2), (player2, player1)]
+            .into_iter()
+            .enumerate()
+        {
+            blocked[index] = self
+                .state
+                .blocked_players
+                .get(blocker)
+                .await
+                .expect("Failed to get blocklist")
+                .unwrap_or_default()
+                .iter()
+                .any(|player| player == other);
+        }
+
+        match blocked {
+            [false, false] => None,
+            [true, false] => Some(player2.to_string()),
+            _ => Some(player1.to_string()),
+        }
+    }
+
+    /// Create an active, stake-free game for a tournament pairing
+    async fn create_tournament_game(
+        &mut self,
+        tournament: &Tournament,
+        player1: String,
+        player2: String,
+        timestamp: u64,
+    ) -> u64 {
+        let id = *self.state.next_game_id.get();
+        self.state.next_game_id.set(id + 1);
+
+        let room_code = format!("T{}-G{}", tournament.id, id);
+        let mut game = Game::new(id, player1, Amount::ZERO, room_code, timestamp);
+        game.currency = tournament.currency;
+        game.opponent = Some(player2);
+        game.status = GameStatus::Active;
+        game.started_at = Some(timestamp);
+        game.tournament_id = Some(tournament.id);
+
+        self.save_game(game).await;
+        id
+    }
+
+    /// Advance both players of a settled game on every running quest
+    async fn record_quest_progress(&mut self, game: &Game, opponent: &str, timestamp: u64) {
+        // Series games carry no stake of their own; the series stake counts
+        let staked = match game.series_id {
+            Some(series_id) => self
+                .state
+                .series
+                .get(&series_id)
+                .await
+                .expect("Failed to get series")
+                .is_some_and(|series| !series.stake.is_zero()),
+            None => !game.stake.is_zero(),
+        };
+        let players = [
+            (game.creator.clone(), game.player1_score, game.player2_score),
+            (opponent.to_string(), game.player2_score, game.player1_score),
+        ];
+
+        for quest_id in self.state.active_quests.get().clone() {
+            let quest = self
+                .state
+                .quests
+                .get(&quest_id)
+                .await
+                .expect("Failed to get quest")
+                .expect("Quest not found");
+            if !quest.is_running(timestamp) {
+                continue;
+            }
+            let period = quest.period(timestamp);
+
+            for (player, scored, conceded) in &players {
+                let won = game.winner.as_ref() == Some(player);
+                let step = quest.goal.progress(won, staked, *scored, *conceded);
+                if step == 0 {
+                    continue;
+                }
+
+                let key = (quest_id, player.clone());
+                let mut progress = self
+                    .state
+                    .quest_progress
+                    .get(&key)
+                    .await
+                    .expect("Failed to get quest progress")
+                    .unwrap_or(QuestProgress {
+                        period,
+                        ..QuestProgress::default()
+                    });
+                if progress.period != period {
+                    // Start the new period, banking an unclaimed completion
+                    progress = QuestProgress {
+                        period,
+                        banked: progress.claimable(quest.goal.target()),
+                        ..QuestProgress::default()
+                    };
+                }
+                progress.progress = progress.progress.saturating_add(step);
+                self.state
+                    .quest_progress
+                    .insert(&key, progress)
+                    .expect("Failed to update quest progress");
+            }
+        }
+    }
+
+    /// Record a tournament game result and move the winner up the bracket
+    async fn advance_tournament(&mut self, tournament_id: u64, game: &Game, timestamp: u64) {
+        let mut tournament = self
+            .state
+            .tournaments
+            .get(&tournament_id)
+            .await
+            .expect("Failed to get tournament")
+            .expect("Tournament not found");
+
+        let Some((round, index)) = tournament.find_match(game.id) else {
+            return;
+        };
+        tournament.rounds[round][index].winner = game.winner.clone();
+
+        self.advance_bracket(&mut tournament, round, index, timestamp)
+            .await;
+
+        self.state
+            .tournaments
+            .insert(&tournament_id, tournament)
+            .expect("Failed to update tournament");
+    }
+
+    /// Move the winner of a decided match up the bracket, starting the next
+    /// match once both its players are known; walkovers advance straight on
+    async fn advance_bracket(
+        &mut self,
+        tournament: &mut Tournament,
+        mut round: usize,
+        mut index: usize,
+        timestamp: u64,
+    ) {
+        loop {
+            let winner = tournament.rounds[round][index].winner.clone();
+            if round + 1 == tournament.rounds.len() {
+                tournament.champion = winner;
+                self.finish_tournament(tournament, timestamp).await;
+                return;
+            }
+
+            // Winners of matches 2k and 2k+1 meet in match k of the next round
+            let next = &mut tournament.rounds[round + 1][index / 2];
+            if index.is_multiple_of(2) {
+                next.player1 = winner;
+            } else {
+                next.player2 = winner;
+            }
+
+            let (Some(player1), Some(player2)) = (next.player1.clone(), next.player2.clone())
+            else {
+                return;
+            };
+            round += 1;
+            index /= 2;
+
+            match self.walkover_winner(&player1, &player2).await {
+                Some(winner) => tournament.rounds[round][index].winner = Some(winner),
+                None => {
+                    let game_id = self
+                        .create_tournament_game(tournament, player1, player2, timestamp)
+                        .await;
+                    tournament.rounds[round][index].game_id = Some(game_id);
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Pay out the prize pool by finishing place and close the tournament
+    async fn finish_tournament(&mut self, tournament: &mut Tournament, timestamp: u64) {
+        // Champion first, then the losers of each round from the final
+        // backwards; players knocked out in the same round tie
+        let mut placements: Vec<Vec<String>> = vec![tournament.champion.iter().cloned().collect()];
+        for matches in tournament.rounds.iter().rev() {
+            let losers = matches
+                .iter()
+                .filter_map(|bracket_match| {
+                    if bracket_match.winner == bracket_match.player1 {
+                        bracket_match.player2.clone()
+                    } else {
+                        bracket_match.player1.clone()
+                    }
+                })
+                .collect();
+            placements.push(losers);
+        }
+
+        let pool = tournament.prize_pool();
+        let payouts = split_prizes(pool, &tournament.prize_split, &placements);
+
+        for player in &tournament.players {
+            if self
+                .has_flagged_opponent(tournament, player, timestamp)
+                .await
+            {
+                continue; // Prize and entry fee stay out of token totals, as for unranked games
+            }
+
+            let prize = payouts
+                .iter()
+                .find(|(winner, _)| winner == player)
+                .map_or(Amount::ZERO, |(_, amount)| *amount);
+
+            self.credit_tokens(player, tournament.currency, prize, tournament.entry_fee)
+                .await;
+        }
+
+        // Update stake pool (remove all entry fees)
+        self.remove_from_stake_pool(tournament.currency, pool).await;
+
+        if let Some(champion) = &tournament.champion {
+            let badge = Badge {
+                achievement: Achievement::TournamentChampion,
+                earned_at: timestamp,
+                game_id: None,
+                tournament_id: Some(tournament.id),
+            };
+            self.update_badges(champion, |badges| badges.award(badge))
+                .await;
+        }
+        self.close_tournament_sponsorship(tournament.id, tournament.champion.clone(), timestamp)
+            .await;
+
+        tournament.payouts = payouts;
+        tournament.status = GameStatus::Completed;
+        tournament.ended_at = Some(timestamp);
+    }
+
+    /// Check if a player met anyone in the bracket they are flagged with
+    async fn has_flagged_opponent(
+        &self,
+        tournament: &Tournament,
+        player: &str,
+        timestamp: u64,
+    ) -> bool {
+        let limits = *self.state.collusion_limits.get();
+        for bracket_match in tournament.rounds.iter().flatten() {
+            let opponent = match (&bracket_match.player1, &bracket_match.player2) {
+                (Some(player1), Some(player2)) if player1 == player => player2,
+                (Some(player1), Some(player2)) if player2 == player => player1,
+                _ => continue,
+            };
+            let flagged = self
+                .state
+                .pair_activity
+                .get(&pair_key(player, opponent))
+                .await
+                .expect("Failed to get pair activity")
+                .is_some_and(|activity| activity.is_flagged(timestamp, &limits));
+            if flagged {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Snapshot final standings, pay the reward pool to the top players and close the season
+    async fn close_season(&mut self, season_id: u64, timestamp: u64) {
+        let mut season = self
+            .state
+            .seasons
+            .get(&season_id)
+            .await
+            .expect("Failed to get season")
+            .expect("Season not found");
+
+        let mut standings = Vec::with_capacity(season.players.len());
+        for player in &season.players {
+            let stats = self
+                .state
+                .load_season_stats(&(season_id, player.clone()))
+                .await
+                .unwrap_or_default();
+            standings.push(SeasonStanding::new(player.clone(), &stats));
+        }
+        Season::rank(&mut standings);
+
+        // Equal shares for the top N, rounding dust to the season leader
+        let winners = standings.len().min(usize::from(season.reward_top_n));
+        if winners > 0 {
+            let share = season.reward_pool.saturating_div(winners as u128);
+            for standing in &mut standings[..winners] {
+                standing.reward = share;
+            }
+            let paid = share.try_mul(winners as u128).expect("Reward overflow");
+            let dust = season
+                .reward_pool
+                .try_sub(paid)
+                .expect("Rewards exceed the pool");
+            standings[0]
+                .reward
+                .try_add_assign(dust)
+                .expect("Reward overflow");
+        }
+
+        for standing in &standings {
+            if !standing.reward.is_zero() {
+                let reward = standing.reward;
+                self.update_reward_totals(&standing.player, |totals| {
+                    totals
+                        .season_rewards
+                        .try_add_assign(reward)
+                        .expect("Reward overflow");
+                })
+                .await;
+            }
+        }
+
+        // Update stake pool (the reward pool is paid out, or returned to the
+        // owner when nobody played)
+        self.remove_from_stake_pool(StakeCurrency::Native, season.reward_pool)
+            .await;
+
+        season.standings = standings;
+        season.ended_at = Some(timestamp);
+        self.state
+            .seasons
+            .insert(&season_id, season)
+            .expect("Failed to update season");
+        self.state.current_season.set(None);
+    }
+}
+
+/// Split a tournament prize pool by finishing place, given the players of
+/// each place from the champion down; tied players split the places they
+/// share equally and rounding dust goes to the first payout
+fn split_prizes(
+    pool: Amount,
+    prize_split: &[u8],
+    placements: &[Vec<String>],
+) -> Vec<(String, Amount)> {
+    let mut payouts: Vec<(String, Amount)> = Vec::new();
+    let mut place = 0;
+    for tied in placements {
+        let percent: u128 = prize_split
+            .iter()
+            .skip(place)
+            .take(tied.len())
+            .map(|&percent| u128::from(percent))
+            .sum();
+        place += tied.len();
+        if percent == 0 {
+            continue;
+        }
+
+        let prize = pool
+            .try_mul(percent)
+            .expect("Prize overflow")
+            .saturating_div(100 * tied.len() as u128);
+        payouts.extend(tied.iter().map(|player| (player.clone(), prize)));
+    }
+
+    // Rounding dust, and the share of places nobody finished in, goes to
+    // the champion
+    let paid: Amount = payouts.iter().map(|(_, amount)| amount).sum();
+    if let Some((_, amount)) = payouts.first_mut() {
+        let dust = pool.try_sub(paid).expect("Prizes exceed the pool");
+        amount.try_add_assign(dust).expect("Prize overflow");
+    }
+
+    payouts
+}
+
+#[cfg(test)]
+mod tests {
+    use air_hockey::QuestGoal;
+    use linera_sdk::{
+        linera_base_types::{AccountOwner, Timestamp},
+        util::BlockingWait,
+    };
+
+    use super::*;
+
+    const START: u64 = 1_000_000_000;
+
+    fn players(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn total(payouts: &[(String, Amount)]) -> Amount {
+        payouts.iter().map(|(_, amount)| amount).sum()
+    }
+
+    #[test]
+    fn prize_split_gives_rounding_dust_to_the_champion() {
+        let pool = Amount::from_attos(1_000);
+        let placements = vec![players(&["a"]), players(&["b"]), players(&["c", "d"])];
+        let payouts = split_prizes(pool, &[50, 30, 20], &placements);
+
+        // Third and fourth place tie and share their 20%
+        assert_eq!(payouts[0], ("a".to_string(), Amount::from_attos(500)));
+        assert_eq!(payouts[1], ("b".to_string(), Amount::from_attos(300)));
+        assert_eq!(payouts[2], ("c".to_string(), Amount::from_attos(100)));
+        assert_eq!(payouts[3], ("d".to_string(), Amount::from_attos(100)));
+        assert_eq!(total(&payouts), pool);
+
+        let pool = Amount::from_attos(1_001);
+        let placements = vec![players(&["a"]), players(&["b"]), players(&["c", "d", "e"])];
+        let payouts = split_prizes(pool, &[40, 30, 10, 10, 10], &placements);
+        assert_eq!(payouts[1].1, Amount::from_attos(300));
+        assert_eq!(payouts[2].1, Amount::from_attos(100));
+        assert_eq!(payouts[0].1, Amount::from_attos(1_001 - 300 - 300));
+        assert_eq!(total(&payouts), pool);
+    }
+
+    #[test]
+    fn prize_split_with_fewer_finishers_than_places_pays_the_whole_pool() {
+        let pool = Amount::from_attos(999);
+        let placements = vec![players(&["a"]), players(&["b"])];
+        let payouts = split_prizes(pool, &[50, 30, 20], &placements);
+
+        assert_eq!(payouts.len(), 2);
+        assert_eq!(payouts[1], ("b".to_string(), Amount::from_attos(299)));
+        assert_eq!(payouts[0], ("a".to_string(), Amount::from_attos(700)));
+        assert_eq!(total(&payouts), pool);
+    }
+
+    #[test]
+    fn prize_split_skips_places_without_a_share() {
+        let pool = Amount::from_attos(100);
+        let placements = vec![players(&["a"]), players(&["b"]), players(&["c", "d"])];
+        let payouts = split_prizes(pool, &[100], &placements);
+
+        assert_eq!(payouts, vec![("a".to_string(), pool)]);
+    }
+
+    fn account(name: &str) -> AccountOwner {
+        AccountOwner::from(CryptoHash::test_hash(name))
+    }
+
+    fn contract(owner: AccountOwner) -> AirHockeyContract {
+        let runtime = ContractRuntime::new()
+            .with_application_parameters(())
+            .with_authenticated_signer(owner)
+            .with_system_time(Timestamp::from(START));
+        let state = AirHockeyState::load(runtime.root_view_storage_context())
+            .blocking_wait()
+            .expect("Failed to load state");
+        let mut contract = AirHockeyContract { state, runtime };
+        contract
+            .instantiate(InstantiationArgument {
+                owner: owner.to_string(),
+            })
+            .blocking_wait();
+        contract
+    }
+
+    fn native_pool(contract: &AirHockeyContract) -> Amount {
+        contract
+            .state
+            .stake_pools
+            .get(&StakeCurrency::Native)
+            .blocking_wait()
+            .expect("Failed to get stake pool")
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn ending_a_quest_keeps_only_earned_rewards_in_escrow() {
+        let owner = account("owner");
+        let alice = account("alice");
+        let mut contract = contract(owner);
+        let quest_id = contract
+            .execute_operation(Operation::DefineQuest {
+                name: "Win twice".to_string(),
+                goal: QuestGoal::WinGames { count: 2 },
+                reward: Amount::from_tokens(10),
+                reward_pool: Amount::from_tokens(100),
+                period_secs: None,
+                duration_secs: None,
+            })
+            .blocking_wait();
+        assert_eq!(native_pool(&contract), Amount::from_tokens(100));
+
+        // Only alice finished without claiming
+        for (player, progress, claimed) in [
+            (alice.to_string(), 2, false),
+            (account("bob").to_string(), 3, true),
+            (account("carol").to_string(), 1, false),
+        ] {
+            contract
+                .state
+                .quest_progress
+                .insert(
+                    &(quest_id, player),
+                    QuestProgress {
+                        period: 0,
+                        progress,
+                        claimed,
+                        banked: 0,
+                    },
+                )
+                .expect("Failed to update quest progress");
+        }
+
+        assert_eq!(
+            contract
+                .execute_operation(Operation::EndQuest { quest_id })
+                .blocking_wait(),
+            quest_id
+        );
+        let quest = contract
+            .state
+            .quests
+            .get(&quest_id)
+            .blocking_wait()
+            .unwrap()
+            .unwrap();
+        assert_eq!(quest.reward_pool, Amount::from_tokens(10));
+        assert_eq!(quest.ends_at, Some(START));
+        assert_eq!(native_pool(&contract), Amount::from_tokens(10));
+        assert!(contract.state.stake_pool_drift().blocking_wait().is_empty());
+
+        contract.runtime.set_authenticated_signer(alice);
+        assert_eq!(
+            contract
+                .execute_operation(Operation::ClaimQuestReward { quest_id })
+                .blocking_wait(),
+            quest_id
+        );
+        assert_eq!(native_pool(&contract), Amount::ZERO);
+        assert!(contract.state.stake_pool_drift().blocking_wait().is_empty());
+    }
+
+    #[test]
+    fn unclaimed_quest_rewards_survive_a_new_period() {
+        let owner = account("owner");
+        let alice = account("alice");
+        let mut contract = contract(owner);
+        let quest_id = contract
+            .execute_operation(Operation::DefineQuest {
+                name: "Daily game".to_string(),
+                goal: QuestGoal::PlayGames { count: 1 },
+                reward: Amount::from_tokens(10),
+                reward_pool: Amount::from_tokens(100),
+                period_secs: Some(60),
+                duration_secs: None,
+            })
+            .blocking_wait();
+
+        let mut game = Game::new(
+            1,
+            alice.to_string(),
+            Amount::ZERO,
+            "ROOM".to_string(),
+            START,
+        );
+        game.opponent = Some("bob".to_string());
+        for day in 0..2 {
+            contract
+                .record_quest_progress(&game, "bob", START + day * 60_000_000)
+                .blocking_wait();
+        }
+        let progress = contract
+            .state
+            .quest_progress
+            .get(&(quest_id, alice.to_string()))
+            .blocking_wait()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (progress.period, progress.progress, progress.banked),
+            (1, 1, 1)
+        );
+
+        contract.runtime.set_authenticated_signer(alice);
+        for _ in 0..2 {
+            assert_eq!(
+                contract
+                    .execute_operation(Operation::ClaimQuestReward { quest_id })
+                    .blocking_wait(),
+                quest_id
+            );
+        }
+        assert_eq!(
+            contract
+                .execute_operation(Operation::ClaimQuestReward { quest_id })
+                .blocking_wait(),
+            0
+        );
+        assert_eq!(native_pool(&contract), Amount::from_tokens(80));
     }
 }
@@ -3,27 +3,407 @@
 //! This crate defines the ABI (Application Binary Interface) for the Air Hockey
 //! staked multiplayer game contract on Linera blockchain.
 
-use async_graphql::{Request, Response};
-use linera_sdk::abi::{ContractAbi, ServiceAbi};
+use air_hockey_physics::{Goal, InputFrame};
+use async_graphql::{Request, Response, SimpleObject};
+use linera_sdk::{
+    abi::{ContractAbi, ServiceAbi},
+    linera_base_types::{Amount, ApplicationId, BcsHashable, ChainId, CryptoHash},
+};
 use serde::{Deserialize, Serialize};
 
 pub struct AirHockeyAbi;
 
+/// Points needed to win when a game does not set its own rules
+pub const DEFAULT_POINTS_TO_WIN: u8 = 7;
+
+/// Longest match a game can set, so a disputed match can still be replayed
+/// within one block (10 minutes)
+pub const MAX_TIME_LIMIT_SECS: u32 = 10 * 60;
+
+/// Token a stake is denominated in
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default,
+)]
+pub enum StakeCurrency {
+    /// The chain's native token
+    #[default]
+    Native,
+    /// A fungible-token application
+    Token(ApplicationId),
+}
+
+impl std::fmt::Display for StakeCurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakeCurrency::Native => write!(f, "native"),
+            StakeCurrency::Token(application_id) => write!(f, "{application_id}"),
+        }
+    }
+}
+
+/// Limits on games between the same two players within a time window;
+/// games beyond them stop counting toward ratings and leaderboards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct CollusionLimits {
+    /// Games a pair can play per window before being flagged
+    pub max_games: u32,
+    /// Net native tokens that can flow one way within a pair per window
+    pub max_net_tokens: Amount,
+    /// Window length in seconds
+    pub window_secs: u64,
+}
+
+impl Default for CollusionLimits {
+    fn default() -> Self {
+        Self {
+            max_games: 10,
+            max_net_tokens: Amount::from_tokens(100),
+            window_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Scoring rules a game is played under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct GameRules {
+    /// Goals needed to win the game
+    pub points_to_win: u8,
+    /// Match length in seconds; None plays until someone reaches `points_to_win`
+    pub time_limit_secs: Option<u32>,
+    /// Whether a game tied at the time limit ends as a draw instead of
+    /// going to sudden death
+    pub allow_draws: bool,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            points_to_win: DEFAULT_POINTS_TO_WIN,
+            time_limit_secs: None,
+            allow_draws: false,
+        }
+    }
+}
+
+impl GameRules {
+    /// Check the rules themselves are playable
+    pub fn is_valid(&self) -> bool {
+        self.points_to_win > 0
+            && self
+                .time_limit_secs
+                .is_none_or(|secs| (1..=MAX_TIME_LIMIT_SECS).contains(&secs))
+    }
+
+    /// Check a final score could have been reached under these rules
+    pub fn is_possible_score(&self, player1_score: u8, player2_score: u8) -> bool {
+        let target = self.points_to_win;
+        if player1_score > target || player2_score > target {
+            return false; // The game stops at the win target
+        }
+
+        match (player1_score == target, player2_score == target) {
+            (true, true) => false, // Only one player can reach the win target
+            (true, false) | (false, true) => true,
+            // Nobody reached the target, so the clock must have run out
+            (false, false) => {
+                self.time_limit_secs.is_some()
+                    && (player1_score != player2_score || self.allow_draws)
+            }
+        }
+    }
+}
+
+/// Result a spectator can bet on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BetOutcome {
+    /// The creator wins
+    Player1,
+    /// The opponent wins
+    Player2,
+    Draw,
+}
+
+impl BetOutcome {
+    /// Outcome of a final score
+    pub fn from_score(player1_score: u8, player2_score: u8) -> Self {
+        match player1_score.cmp(&player2_score) {
+            std::cmp::Ordering::Greater => BetOutcome::Player1,
+            std::cmp::Ordering::Less => BetOutcome::Player2,
+            std::cmp::Ordering::Equal => BetOutcome::Draw,
+        }
+    }
+}
+
+/// What a player has to do to complete a quest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuestGoal {
+    /// Play `count` games
+    PlayGames { count: u32 },
+    /// Win `count` games
+    WinGames { count: u32 },
+    /// Win `count` games with a non-zero stake
+    WinStakedGames { count: u32 },
+    /// Win `count` games without conceding a goal
+    Shutouts { count: u32 },
+    /// Score `count` goals
+    ScoreGoals { count: u32 },
+}
+
+impl QuestGoal {
+    /// Name of the goal kind, e.g. "WinGames"
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QuestGoal::PlayGames { .. } => "PlayGames",
+            QuestGoal::WinGames { .. } => "WinGames",
+            QuestGoal::WinStakedGames { .. } => "WinStakedGames",
+            QuestGoal::Shutouts { .. } => "Shutouts",
+            QuestGoal::ScoreGoals { .. } => "ScoreGoals",
+        }
+    }
+
+    /// Progress needed to complete the quest
+    pub fn target(&self) -> u32 {
+        match *self {
+            QuestGoal::PlayGames { count }
+            | QuestGoal::WinGames { count }
+            | QuestGoal::WinStakedGames { count }
+            | QuestGoal::Shutouts { count }
+            | QuestGoal::ScoreGoals { count } => count,
+        }
+    }
+
+    /// Progress one finished game adds for a player
+    pub fn progress(&self, won: bool, staked: bool, scored: u8, conceded: u8) -> u32 {
+        let counts = match self {
+            QuestGoal::PlayGames { .. } => true,
+            QuestGoal::WinGames { .. } => won,
+            QuestGoal::WinStakedGames { .. } => won && staked,
+            QuestGoal::Shutouts { .. } => won && conceded == 0,
+            QuestGoal::ScoreGoals { .. } => return u32::from(scored),
+        };
+        u32::from(counts)
+    }
+}
+
+/// A goal in a game's score timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
+pub struct GoalEvent {
+    /// Scoring player: 1 for the creator, 2 for the opponent
+    pub scorer: u8,
+    /// Microseconds since the game started
+    pub offset_micros: u64,
+}
+
+impl GoalEvent {
+    /// Check a timeline is in order and adds up to the final score
+    pub fn is_consistent(timeline: &[GoalEvent], player1_score: u8, player2_score: u8) -> bool {
+        let in_order = timeline
+            .windows(2)
+            .all(|pair| pair[0].offset_micros <= pair[1].offset_micros);
+        let goals_by = |scorer| timeline.iter().filter(|goal| goal.scorer == scorer).count();
+
+        in_order
+            && goals_by(1) == usize::from(player1_score)
+            && goals_by(2) == usize::from(player2_score)
+            && timeline.len() == usize::from(player1_score) + usize::from(player2_score)
+    }
+}
+
+/// A player's paddle inputs for a disputed game
+///
+/// Players first commit `CryptoHash::new(&log)`, then reveal the log itself.
+/// The salt keeps the commitment from being guessed before the reveal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputLog {
+    pub game_id: u64,
+    pub salt: u64,
+    pub frames: Vec<InputFrame>,
+}
+
+impl BcsHashable<'_> for InputLog {}
+
+/// Full record of a played match, kept off-chain by the game server
+///
+/// `SubmitResult` can carry `CryptoHash::new(&replay)`, so anyone holding
+/// the replay file can show it matches the settled game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchReplay {
+    pub game_id: u64,
+    /// Paddle inputs of both players, in tick order
+    pub frames: Vec<InputFrame>,
+    /// Goals in the order they were scored
+    pub goals: Vec<Goal>,
+    /// Wall-clock start of the match in microseconds
+    pub started_at: u64,
+    /// Wall-clock end of the match in microseconds
+    pub ended_at: u64,
+}
+
+impl BcsHashable<'_> for MatchReplay {}
+
 /// Contract operations
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Operation {
-    /// Create a new staked game
-    CreateGame { stake: u64, room_code: String },
+    /// Create a new staked game; `min_reliability` (per mille) restricts
+    /// who can join
+    CreateGame {
+        stake: Amount,
+        currency: StakeCurrency,
+        room_code: String,
+        rules: GameRules,
+        min_reliability: Option<u16>,
+    },
     /// Join an existing game
     JoinGame { game_id: u64 },
-    /// Submit game result
+    /// Submit game result, optionally committing to the full match replay
+    /// and recording when each goal was scored. The first submission waits
+    /// for the other player: the same score from them settles the game, a
+    /// different one opens a dispute, and otherwise the result can be
+    /// finalized once the confirmation window passes
     SubmitResult {
         game_id: u64,
         player1_score: u8,
         player2_score: u8,
+        replay_hash: Option<CryptoHash>,
+        timeline: Option<Vec<GoalEvent>>,
     },
     /// Cancel a waiting game
     CancelGame { game_id: u64 },
+    /// Concede an active game, handing the opponent the win
+    ForfeitGame { game_id: u64 },
+    /// Void an active game of yours whose result never arrived, refunding
+    /// both stakes
+    ExpireGame { game_id: u64 },
+    /// Bet on the outcome of an active game in its side pool; open to
+    /// anyone but the two players until shortly after the game starts
+    PlaceBet {
+        game_id: u64,
+        outcome: BetOutcome,
+        amount: Amount,
+    },
+    /// Add a bonus paid to the winner of a waiting or active game, in the
+    /// game's currency; refunded on a draw or if the game is cancelled
+    SponsorGame { game_id: u64, amount: Amount },
+    /// Settle an active game with its pending result once the confirmation
+    /// window has passed without a dispute
+    FinalizeResult { game_id: u64 },
+    /// Dispute an active game, or the result the other player submitted
+    /// while its confirmation window is open; the score is then settled by
+    /// replaying both players' input logs
+    DisputeResult { game_id: u64 },
+    /// Commit the hash of your input log for a disputed game
+    CommitInputLog {
+        game_id: u64,
+        commitment: CryptoHash,
+    },
+    /// Reveal your committed input log; the match is replayed once both
+    /// logs are revealed
+    RevealInputLog { log: InputLog },
+    /// Settle a dispute whose deadline passed without both logs revealed:
+    /// the side that revealed, or else committed, wins by forfeit, and when
+    /// both got equally far the disputer forfeits
+    ClaimDisputeTimeout { game_id: u64 },
+    /// Create a best-of-N series, escrowing the stake once for all games
+    CreateSeries {
+        stake: Amount,
+        currency: StakeCurrency,
+        room_code: String,
+        best_of: u8,
+    },
+    /// Join a waiting series and start its first game
+    JoinSeries { series_id: u64 },
+    /// Cancel a waiting series
+    CancelSeries { series_id: u64 },
+    /// Create a single-elimination tournament; `prize_split` holds the
+    /// percentage of the prize pool paid to each finishing place. Players
+    /// knocked out in the same round share their places equally, so with
+    /// `[50, 30, 10, 10]` both semifinal losers get 10%
+    CreateTournament {
+        entry_fee: Amount,
+        currency: StakeCurrency,
+        size: u8,
+        prize_split: Vec<u8>,
+    },
+    /// Register for a tournament, paying the entry fee; the bracket is
+    /// seeded by rating once the field is full
+    RegisterTournament { tournament_id: u64 },
+    /// Cancel a tournament that is still open for registration
+    CancelTournament { tournament_id: u64 },
+    /// Add a bonus paid to the champion of an open or running tournament,
+    /// in its currency; refunded if the tournament is cancelled
+    SponsorTournament { tournament_id: u64, amount: Amount },
+    /// Open a new season (owner only); `reward_pool`, in native tokens, is
+    /// escrowed and shared by the top `reward_top_n` players when the
+    /// season closes
+    OpenSeason {
+        reward_pool: Amount,
+        reward_top_n: u8,
+    },
+    /// Close the current season and snapshot its standings (owner only)
+    CloseSeason,
+    /// Define a quest paying `reward` native tokens per completion until
+    /// `reward_pool`, which is escrowed, runs out (owner only); progress and
+    /// claims reset every `period_secs` when set, e.g. daily quests, and
+    /// unclaimed completions carry over
+    DefineQuest {
+        name: String,
+        goal: QuestGoal,
+        reward: Amount,
+        reward_pool: Amount,
+        period_secs: Option<u64>,
+        duration_secs: Option<u64>,
+    },
+    /// Stop tracking a quest and release the reward pool beyond the rewards
+    /// already earned, which can still be claimed (owner only)
+    EndQuest { quest_id: u64 },
+    /// Claim the reward of a completed quest
+    ClaimQuestReward { quest_id: u64 },
+    /// Walk up to `max_games` games, folding finished games older than the
+    /// retention window into per-player archive summaries and deleting their
+    /// full records (owner only); `hash_history` also chains each record
+    /// into the players' history hashes
+    ArchiveGames { max_games: u32, hash_history: bool },
+    /// Reset every stake pool to the stakes actually in escrow, recording
+    /// any drift found (owner only)
+    Reconcile,
+    /// Rewrite up to `max_records` stored games and player stats in the
+    /// current schema layout, moving entries out of the legacy unversioned
+    /// maps (owner only); records also upgrade when read
+    MigrateRecords { max_records: u32 },
+    /// Change the anti-collusion limits (owner only)
+    SetCollusionLimits { limits: CollusionLimits },
+    /// Create or update your player profile; nicknames are unique
+    SetProfile {
+        nickname: String,
+        avatar: Option<String>,
+        country: Option<String>,
+    },
+    /// Delete your player profile and release its nickname
+    ClearProfile,
+    /// Add a player to your friends list
+    AddFriend { player: String },
+    /// Remove a player from your friends list
+    RemoveFriend { player: String },
+    /// Challenge a player directly, escrowing your stake; the challenged
+    /// player is notified on `opponent_chain`
+    Challenge {
+        opponent: String,
+        opponent_chain: ChainId,
+        stake: Amount,
+        currency: StakeCurrency,
+        rules: GameRules,
+        room_code: String,
+    },
+    /// Accept a challenge, escrowing your stake and starting the game
+    AcceptChallenge { challenge_id: u64 },
+    /// Decline a challenge, refunding the challenger
+    DeclineChallenge { challenge_id: u64 },
+    /// Withdraw a challenge you issued before it is answered
+    WithdrawChallenge { challenge_id: u64 },
+    /// Block a player; blocked pairs are never matched against each other
+    BlockPlayer { player: String },
+    /// Unblock a player
+    UnblockPlayer { player: String },
 }
 
 /// Cross-chain messages
@@ -33,14 +413,11 @@ pub enum Message {
     GameCreated {
         game_id: u64,
         creator: String,
-        stake: u64,
+        stake: Amount,
         room_code: String,
     },
     /// Game joined notification
-    GameJoined {
-        game_id: u64,
-        opponent: String,
-    },
+    GameJoined { game_id: u64, opponent: String },
     /// Game completed notification
     GameCompleted {
         game_id: u64,
@@ -49,8 +426,17 @@ pub enum Message {
         player2_score: u8,
     },
     /// Game cancelled notification
-    GameCancelled {
-        game_id: u64,
+    GameCancelled { game_id: u64 },
+    /// Direct challenge notification, sent to the challenged player's chain;
+    /// the challenge is answered on the chain that issued it
+    ChallengeIssued {
+        challenge_id: u64,
+        challenger: String,
+        opponent: String,
+        stake: Amount,
+        currency: StakeCurrency,
+        rules: GameRules,
+        room_code: String,
     },
 }
 
@@ -62,10 +448,58 @@ pub struct InstantiationArgument {
 
 impl ContractAbi for AirHockeyAbi {
     type Operation = Operation;
-    type Response = u64; // Returns game_id or 0
+    type Response = u64; // Returns the affected game, series, tournament or season ID, 1 for other successes, or 0
 }
 
 impl ServiceAbi for AirHockeyAbi {
     type Query = Request;
     type QueryResponse = Response;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(allow_draws: bool) -> GameRules {
+        GameRules {
+            points_to_win: 7,
+            time_limit_secs: Some(300),
+            allow_draws,
+        }
+    }
+
+    #[test]
+    fn score_must_reach_the_win_target_without_passing_it() {
+        let rules = GameRules::default();
+
+        assert!(rules.is_possible_score(7, 0));
+        assert!(rules.is_possible_score(6, 7));
+        assert!(!rules.is_possible_score(8, 3));
+        assert!(!rules.is_possible_score(2, 8));
+        assert!(!rules.is_possible_score(7, 7));
+    }
+
+    #[test]
+    fn untimed_games_end_only_at_the_win_target() {
+        let rules = GameRules::default();
+
+        assert!(!rules.is_possible_score(6, 5));
+        assert!(!rules.is_possible_score(0, 0));
+    }
+
+    #[test]
+    fn timed_games_can_end_short_of_the_target() {
+        assert!(timed(false).is_possible_score(6, 5));
+        assert!(timed(false).is_possible_score(0, 1));
+        assert!(timed(false).is_possible_score(7, 6));
+        assert!(!timed(false).is_possible_score(8, 6));
+    }
+
+    #[test]
+    fn timed_games_end_level_only_when_draws_are_allowed() {
+        assert!(!timed(false).is_possible_score(3, 3));
+        assert!(timed(true).is_possible_score(3, 3));
+        assert!(timed(true).is_possible_score(0, 0));
+        assert!(!timed(true).is_possible_score(7, 7));
+    }
+}
@@ -6,24 +6,59 @@
 
 mod state;
 
+use std::{collections::BTreeSet, sync::Arc};
+
 use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
 use linera_sdk::{
     abi::WithServiceAbi,
-    views::{RootView, View},
+    linera_base_types::{Amount, CryptoHash},
+    views::View,
     Service, ServiceRuntime,
 };
 use serde::{Deserialize, Serialize};
 
-use air_hockey::AirHockeyAbi;
-use crate::state::{AirHockeyState, Game, GameStatus, PlayerStats};
+use crate::state::{
+    pair_key, AirHockeyState, BracketMatch, Challenge, Game, GameStatus, HeadToHead, PairActivity,
+    PlayerArchive, PlayerBadges, PlayerProfile, PlayerStats, PoolDrift, Quest, Reconciliation,
+    ReplayVerdict, RewardTotals, Season, SeasonStanding, Series, SidePool, Sponsorship,
+    TokenTotals, Tournament, SCHEMA_VERSION,
+};
+use air_hockey::{AirHockeyAbi, BetOutcome, CollusionLimits, GameRules, GoalEvent, StakeCurrency};
+
+/// Most entries a leaderboard query returns
+const MAX_LEADERBOARD_ENTRIES: u32 = 100;
+
+/// Replay verdict for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayVerdictInfo {
+    pub player1_score: u8,
+    pub player2_score: u8,
+    pub ticks: u32,
+    pub settled: bool,
+}
+
+impl From<ReplayVerdict> for ReplayVerdictInfo {
+    fn from(verdict: ReplayVerdict) -> Self {
+        Self {
+            player1_score: verdict.player1_score,
+            player2_score: verdict.player2_score,
+            ticks: verdict.ticks,
+            settled: verdict.settled,
+        }
+    }
+}
 
 /// Game info for GraphQL responses
 #[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
 pub struct GameInfo {
     pub id: u64,
     pub creator: String,
+    pub creator_nickname: Option<String>,
     pub opponent: Option<String>,
-    pub stake: String,
+    pub opponent_nickname: Option<String>,
+    pub stake: Amount,
+    /// "native" or the token application ID
+    pub currency: String,
     pub status: String,
     pub winner: Option<String>,
     pub created_at: u64,
@@ -32,15 +67,31 @@ pub struct GameInfo {
     pub player1_score: u8,
     pub player2_score: u8,
     pub room_code: String,
+    pub series_id: Option<u64>,
+    pub tournament_id: Option<u64>,
+    pub rules: GameRules,
+    pub disputed_by: Option<String>,
+    pub disputed_at: Option<u64>,
+    pub dispute_deadline: Option<u64>,
+    pub player1_input_commitment: Option<CryptoHash>,
+    pub player2_input_commitment: Option<CryptoHash>,
+    pub replay_verdict: Option<ReplayVerdictInfo>,
+    pub replay_hash: Option<CryptoHash>,
+    pub min_reliability: Option<u16>,
 }
 
 impl From<Game> for GameInfo {
     fn from(game: Game) -> Self {
+        let dispute_deadline = game.dispute_deadline();
+
         Self {
             id: game.id,
             creator: game.creator,
+            creator_nickname: None,
             opponent: game.opponent,
-            stake: game.stake.to_string(),
+            opponent_nickname: None,
+            stake: game.stake,
+            currency: game.currency.to_string(),
             status: format!("{:?}", game.status),
             winner: game.winner,
             created_at: game.created_at,
@@ -49,6 +100,385 @@ impl From<Game> for GameInfo {
             player1_score: game.player1_score,
             player2_score: game.player2_score,
             room_code: game.room_code,
+            series_id: game.series_id,
+            tournament_id: game.tournament_id,
+            rules: game.rules,
+            dispute_deadline,
+            disputed_by: game.disputed_by,
+            disputed_at: game.disputed_at,
+            player1_input_commitment: game.input_commitments[0],
+            player2_input_commitment: game.input_commitments[1],
+            replay_verdict: game.replay_verdict.map(ReplayVerdictInfo::from),
+            replay_hash: game.replay_hash,
+            min_reliability: game.min_reliability,
+        }
+    }
+}
+
+/// Series info for GraphQL responses, with per-game and aggregate scores
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesInfo {
+    pub id: u64,
+    pub creator: String,
+    pub opponent: Option<String>,
+    pub stake: Amount,
+    /// "native" or the token application ID
+    pub currency: String,
+    pub best_of: u8,
+    pub status: String,
+    pub winner: Option<String>,
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub ended_at: Option<u64>,
+    pub player1_wins: u8,
+    pub player2_wins: u8,
+    pub player1_goals: u32,
+    pub player2_goals: u32,
+    pub games: Vec<GameInfo>,
+    pub room_code: String,
+}
+
+impl From<Series> for SeriesInfo {
+    fn from(series: Series) -> Self {
+        Self {
+            id: series.id,
+            creator: series.creator,
+            opponent: series.opponent,
+            stake: series.stake,
+            currency: series.currency.to_string(),
+            best_of: series.best_of,
+            status: format!("{:?}", series.status),
+            winner: series.winner,
+            created_at: series.created_at,
+            started_at: series.started_at,
+            ended_at: series.ended_at,
+            player1_wins: series.player1_wins,
+            player2_wins: series.player2_wins,
+            player1_goals: 0,
+            player2_goals: 0,
+            games: Vec::new(),
+            room_code: series.room_code,
+        }
+    }
+}
+
+impl SeriesInfo {
+    /// Attach the series games and total up the goals scored
+    fn with_games(mut self, games: Vec<GameInfo>) -> Self {
+        self.player1_goals = games.iter().map(|g| u32::from(g.player1_score)).sum();
+        self.player2_goals = games.iter().map(|g| u32::from(g.player2_score)).sum();
+        self.games = games;
+        self
+    }
+}
+
+/// Bracket pairing for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct BracketMatchInfo {
+    pub player1: Option<String>,
+    pub player2: Option<String>,
+    pub game_id: Option<u64>,
+    pub winner: Option<String>,
+    pub player1_score: Option<u8>,
+    pub player2_score: Option<u8>,
+}
+
+impl From<BracketMatch> for BracketMatchInfo {
+    fn from(bracket_match: BracketMatch) -> Self {
+        Self {
+            player1: bracket_match.player1,
+            player2: bracket_match.player2,
+            game_id: bracket_match.game_id,
+            winner: bracket_match.winner,
+            player1_score: None,
+            player2_score: None,
+        }
+    }
+}
+
+/// Prize payout for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PayoutInfo {
+    pub player: String,
+    pub amount: Amount,
+}
+
+/// Tournament info for GraphQL responses, with the full bracket tree
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentInfo {
+    pub id: u64,
+    pub organizer: String,
+    pub entry_fee: Amount,
+    /// "native" or the token application ID
+    pub currency: String,
+    pub size: u8,
+    pub prize_split: Vec<u8>,
+    pub prize_pool: Amount,
+    pub status: String,
+    pub players: Vec<String>,
+    /// Bracket rounds, first round first
+    pub rounds: Vec<Vec<BracketMatchInfo>>,
+    pub champion: Option<String>,
+    pub payouts: Vec<PayoutInfo>,
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub ended_at: Option<u64>,
+}
+
+impl From<Tournament> for TournamentInfo {
+    fn from(tournament: Tournament) -> Self {
+        Self {
+            id: tournament.id,
+            organizer: tournament.organizer.clone(),
+            entry_fee: tournament.entry_fee,
+            currency: tournament.currency.to_string(),
+            size: tournament.size,
+            prize_pool: tournament.prize_pool(),
+            prize_split: tournament.prize_split,
+            status: format!("{:?}", tournament.status),
+            players: tournament.players,
+            rounds: tournament
+                .rounds
+                .into_iter()
+                .map(|matches| matches.into_iter().map(BracketMatchInfo::from).collect())
+                .collect(),
+            champion: tournament.champion,
+            payouts: tournament
+                .payouts
+                .into_iter()
+                .map(|(player, amount)| PayoutInfo { player, amount })
+                .collect(),
+            created_at: tournament.created_at,
+            started_at: tournament.started_at,
+            ended_at: tournament.ended_at,
+        }
+    }
+}
+
+/// Spectator bet for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SideBetInfo {
+    pub bettor: String,
+    /// "Player1", "Player2" or "Draw"
+    pub outcome: String,
+    pub amount: Amount,
+    pub payout: Option<Amount>,
+    pub placed_at: u64,
+}
+
+/// Result waiting for the other player's confirmation, for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PendingResultInfo {
+    pub game_id: u64,
+    pub player1_score: u8,
+    pub player2_score: u8,
+    pub submitted_by: String,
+    pub submitted_at: u64,
+    /// Time until which the other player can dispute the result
+    pub confirmation_deadline: u64,
+    pub replay_hash: Option<CryptoHash>,
+    pub timeline: Option<Vec<GoalEvent>>,
+}
+
+/// Game side pool for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SidePoolInfo {
+    pub game_id: u64,
+    /// "native" or the token application ID
+    pub currency: String,
+    pub total: Amount,
+    pub total_on_player1: Amount,
+    pub total_on_player2: Amount,
+    pub total_on_draw: Amount,
+    pub bets: Vec<SideBetInfo>,
+    pub refunded: bool,
+    pub closed_at: Option<u64>,
+}
+
+impl From<SidePool> for SidePoolInfo {
+    fn from(pool: SidePool) -> Self {
+        Self {
+            game_id: pool.game_id,
+            currency: pool.currency.to_string(),
+            total: pool.total(),
+            total_on_player1: pool.total_on(BetOutcome::Player1),
+            total_on_player2: pool.total_on(BetOutcome::Player2),
+            total_on_draw: pool.total_on(BetOutcome::Draw),
+            bets: pool
+                .bets
+                .into_iter()
+                .map(|bet| SideBetInfo {
+                    bettor: bet.bettor,
+                    outcome: format!("{:?}", bet.outcome),
+                    amount: bet.amount,
+                    payout: bet.payout,
+                    placed_at: bet.placed_at,
+                })
+                .collect(),
+            refunded: pool.refunded,
+            closed_at: pool.closed_at,
+        }
+    }
+}
+
+/// Sponsor contribution for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SponsorInfo {
+    pub sponsor: String,
+    pub amount: Amount,
+}
+
+/// Sponsor bonus of a game or tournament for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SponsorshipInfo {
+    /// "native" or the token application ID
+    pub currency: String,
+    pub total: Amount,
+    pub sponsors: Vec<SponsorInfo>,
+    pub winner: Option<String>,
+    pub refunded: bool,
+    pub closed_at: Option<u64>,
+}
+
+impl From<Sponsorship> for SponsorshipInfo {
+    fn from(sponsorship: Sponsorship) -> Self {
+        Self {
+            currency: sponsorship.currency.to_string(),
+            total: sponsorship.total(),
+            sponsors: sponsorship
+                .sponsors
+                .into_iter()
+                .map(|(sponsor, amount)| SponsorInfo { sponsor, amount })
+                .collect(),
+            winner: sponsorship.winner,
+            refunded: sponsorship.refunded,
+            closed_at: sponsorship.closed_at,
+        }
+    }
+}
+
+/// Quest info for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct QuestInfo {
+    pub id: u64,
+    pub name: String,
+    /// Goal kind, e.g. "WinGames"
+    pub goal: String,
+    /// Progress needed to complete the quest
+    pub target: u32,
+    pub reward: Amount,
+    pub reward_pool: Amount,
+    pub period_secs: Option<u64>,
+    pub starts_at: u64,
+    pub ends_at: Option<u64>,
+    pub claims: u32,
+}
+
+impl From<Quest> for QuestInfo {
+    fn from(quest: Quest) -> Self {
+        Self {
+            id: quest.id,
+            name: quest.name,
+            goal: quest.goal.kind().to_string(),
+            target: quest.goal.target(),
+            reward: quest.reward,
+            reward_pool: quest.reward_pool,
+            period_secs: quest.period_secs,
+            starts_at: quest.starts_at,
+            ends_at: quest.ends_at,
+            claims: quest.claims,
+        }
+    }
+}
+
+/// A player's progress on a quest for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct QuestProgressInfo {
+    pub quest: QuestInfo,
+    /// Period the progress was recorded in; progress from an earlier
+    /// period no longer grows but stays claimable once completed
+    pub period: u64,
+    pub progress: u32,
+    pub completed: bool,
+    pub claimed: bool,
+    /// Rewards the player can still claim
+    pub claimable: u32,
+}
+
+/// Stake pool that differs from the stakes in escrow, for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PoolDriftInfo {
+    /// "native" or the token application ID
+    pub currency: String,
+    pub recorded: Amount,
+    pub expected: Amount,
+}
+
+impl From<PoolDrift> for PoolDriftInfo {
+    fn from(drift: PoolDrift) -> Self {
+        Self {
+            currency: drift.currency.to_string(),
+            recorded: drift.recorded,
+            expected: drift.expected,
+        }
+    }
+}
+
+/// Last owner reconciliation of the stake pools for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationInfo {
+    pub reconciled_at: u64,
+    /// Pools that were corrected, with their values before the fix
+    pub drift: Vec<PoolDriftInfo>,
+}
+
+impl From<Reconciliation> for ReconciliationInfo {
+    fn from(reconciliation: Reconciliation) -> Self {
+        Self {
+            reconciled_at: reconciliation.reconciled_at,
+            drift: reconciliation
+                .drift
+                .into_iter()
+                .map(PoolDriftInfo::from)
+                .collect(),
+        }
+    }
+}
+
+/// Stored schema version and record migration progress for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaInfo {
+    /// Layout version of the stored state
+    pub version: u32,
+    /// Layout version this build writes
+    pub latest_version: u32,
+    /// Next game ID the record migration will check
+    pub migration_cursor: u64,
+}
+
+/// Escrowed stakes in one currency for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct StakePoolInfo {
+    /// "native" or the token application ID
+    pub currency: String,
+    pub amount: Amount,
+}
+
+/// Tokens won and lost in one currency for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct TokenTotalsInfo {
+    /// "native" or the token application ID
+    pub currency: String,
+    pub won: Amount,
+    pub lost: Amount,
+}
+
+impl From<TokenTotals> for TokenTotalsInfo {
+    fn from(totals: TokenTotals) -> Self {
+        Self {
+            currency: totals.currency.to_string(),
+            won: totals.won,
+            lost: totals.lost,
         }
     }
 }
@@ -59,9 +489,25 @@ pub struct PlayerStatsInfo {
     pub games_played: u64,
     pub wins: u64,
     pub losses: u64,
-    pub tokens_won: String,
-    pub tokens_lost: String,
+    /// Native tokens won
+    pub tokens_won: Amount,
+    /// Native tokens lost
+    pub tokens_lost: Amount,
+    /// Tokens won and lost in every currency played
+    pub tokens: Vec<TokenTotalsInfo>,
     pub win_rate: f64,
+    pub rating: u32,
+    pub forfeits: u64,
+    pub expired_games: u64,
+    pub late_cancellations: u64,
+    pub lost_disputes: u64,
+    /// Share of commitments honoured, from 0.0 to 1.0
+    pub reliability: f64,
+    /// Run of identical ranked results: positive for wins, negative for losses
+    pub current_streak: i32,
+    pub longest_win_streak: u32,
+    /// Last ranked results, oldest first: "Win", "Loss" or "Draw"
+    pub recent_results: Vec<String>,
 }
 
 impl From<PlayerStats> for PlayerStatsInfo {
@@ -71,18 +517,318 @@ impl From<PlayerStats> for PlayerStatsInfo {
         } else {
             0.0
         };
+        // Until the ring is full `next` equals its length, so nothing has wrapped
+        let recent = &stats.recent_results;
+        let (newer, older) = recent.slots.split_at(recent.next.min(recent.slots.len()));
+        let recent_results = older
+            .iter()
+            .chain(newer)
+            .map(|result| format!("{result:?}"))
+            .collect();
+
+        let native = stats
+            .tokens
+            .iter()
+            .find(|totals| totals.currency == StakeCurrency::Native)
+            .copied()
+            .unwrap_or_default();
 
         Self {
             games_played: stats.games_played,
             wins: stats.wins,
             losses: stats.losses,
-            tokens_won: stats.tokens_won.to_string(),
-            tokens_lost: stats.tokens_lost.to_string(),
+            tokens_won: native.won,
+            tokens_lost: native.lost,
             win_rate,
+            rating: stats.rating,
+            forfeits: stats.forfeits,
+            expired_games: stats.expired_games,
+            late_cancellations: stats.late_cancellations,
+            lost_disputes: stats.lost_disputes,
+            reliability: f64::from(stats.reliability()) / 1000.0,
+            current_streak: stats.current_streak,
+            longest_win_streak: stats.longest_win_streak,
+            recent_results,
+            tokens: stats
+                .tokens
+                .into_iter()
+                .map(TokenTotalsInfo::from)
+                .collect(),
+        }
+    }
+}
+
+/// Streak leaderboard entry for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct StreakEntryInfo {
+    pub rank: u32,
+    pub player: String,
+    pub nickname: Option<String>,
+    pub longest_win_streak: u32,
+    pub current_streak: i32,
+}
+
+/// Season and quest rewards of a player for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct RewardTotalsInfo {
+    pub season_rewards: Amount,
+    pub quest_rewards: Amount,
+}
+
+impl From<RewardTotals> for RewardTotalsInfo {
+    fn from(totals: RewardTotals) -> Self {
+        Self {
+            season_rewards: totals.season_rewards,
+            quest_rewards: totals.quest_rewards,
         }
     }
 }
 
+/// Season standing for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonStandingInfo {
+    pub rank: u32,
+    pub player: String,
+    pub nickname: Option<String>,
+    pub rating: u32,
+    pub games_played: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub reward: Amount,
+}
+
+impl From<SeasonStanding> for SeasonStandingInfo {
+    fn from(standing: SeasonStanding) -> Self {
+        Self {
+            rank: 0,
+            player: standing.player,
+            nickname: None,
+            rating: standing.rating,
+            games_played: standing.games_played,
+            wins: standing.wins,
+            losses: standing.losses,
+            reward: standing.reward,
+        }
+    }
+}
+
+/// Season info for GraphQL responses; standings are live while the season is open
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonInfo {
+    pub id: u64,
+    pub is_open: bool,
+    pub reward_pool: Amount,
+    pub reward_top_n: u8,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub standings: Vec<SeasonStandingInfo>,
+}
+
+impl From<Season> for SeasonInfo {
+    fn from(season: Season) -> Self {
+        Self {
+            id: season.id,
+            is_open: season.ended_at.is_none(),
+            reward_pool: season.reward_pool,
+            reward_top_n: season.reward_top_n,
+            started_at: season.started_at,
+            ended_at: season.ended_at,
+            standings: season
+                .standings
+                .into_iter()
+                .zip(1..)
+                .map(|(standing, rank)| SeasonStandingInfo {
+                    rank,
+                    ..standing.into()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Collusion window of a flagged pair of players for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PairActivityInfo {
+    pub player_a: String,
+    pub player_b: String,
+    pub window_started_at: u64,
+    pub games: u32,
+    /// Native tokens won by player A in the window
+    pub player_a_tokens_won: Amount,
+    /// Native tokens won by player B in the window
+    pub player_b_tokens_won: Amount,
+    pub flagged_at: Option<u64>,
+}
+
+impl From<((String, String), PairActivity)> for PairActivityInfo {
+    fn from(((player_a, player_b), activity): ((String, String), PairActivity)) -> Self {
+        Self {
+            player_a,
+            player_b,
+            window_started_at: activity.window_started_at,
+            games: activity.games,
+            player_a_tokens_won: activity.tokens_won[0],
+            player_b_tokens_won: activity.tokens_won[1],
+            flagged_at: activity.flagged_at,
+        }
+    }
+}
+
+/// Head-to-head record between two players for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct HeadToHeadInfo {
+    pub player_a: String,
+    pub player_b: String,
+    pub games_played: u64,
+    pub player_a_wins: u64,
+    pub player_b_wins: u64,
+    pub draws: u64,
+    /// Native tokens won by player A
+    pub player_a_tokens_won: Amount,
+    /// Native tokens won by player B
+    pub player_b_tokens_won: Amount,
+    /// Most recent game IDs, oldest first
+    pub recent_game_ids: Vec<u64>,
+}
+
+impl From<((String, String), HeadToHead)> for HeadToHeadInfo {
+    fn from(((player_a, player_b), record): ((String, String), HeadToHead)) -> Self {
+        Self {
+            player_a,
+            player_b,
+            games_played: record.games_played,
+            player_a_wins: record.first_wins,
+            player_b_wins: record.second_wins,
+            draws: record.draws,
+            player_a_tokens_won: record.tokens_won[0],
+            player_b_tokens_won: record.tokens_won[1],
+            recent_game_ids: record.recent_game_ids,
+        }
+    }
+}
+
+/// Summary of a player's archived games for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerArchiveInfo {
+    pub games_completed: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub draws: u64,
+    pub games_cancelled: u64,
+    pub history_hash: Option<CryptoHash>,
+    pub last_game_id: Option<u64>,
+}
+
+impl From<PlayerArchive> for PlayerArchiveInfo {
+    fn from(archive: PlayerArchive) -> Self {
+        Self {
+            games_completed: archive.games_completed,
+            wins: archive.wins,
+            losses: archive.losses,
+            draws: archive.draws,
+            games_cancelled: archive.games_cancelled,
+            history_hash: archive.history_hash,
+            last_game_id: archive.last_game_id,
+        }
+    }
+}
+
+/// Player profile for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfileInfo {
+    pub nickname: String,
+    pub avatar: Option<String>,
+    pub country: Option<String>,
+    pub created_at: u64,
+}
+
+impl From<PlayerProfile> for PlayerProfileInfo {
+    fn from(profile: PlayerProfile) -> Self {
+        Self {
+            nickname: profile.nickname,
+            avatar: profile.avatar,
+            country: profile.country,
+            created_at: profile.created_at,
+        }
+    }
+}
+
+/// Achievement badge for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct BadgeInfo {
+    /// Achievement name, e.g. "FirstWin"
+    pub achievement: String,
+    pub earned_at: u64,
+    pub game_id: Option<u64>,
+    pub tournament_id: Option<u64>,
+}
+
+/// A player's badges for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerBadgesInfo {
+    pub badges: Vec<BadgeInfo>,
+}
+
+impl From<PlayerBadges> for PlayerBadgesInfo {
+    fn from(badges: PlayerBadges) -> Self {
+        Self {
+            badges: badges
+                .badges
+                .into_iter()
+                .map(|badge| BadgeInfo {
+                    achievement: format!("{:?}", badge.achievement),
+                    earned_at: badge.earned_at,
+                    game_id: badge.game_id,
+                    tournament_id: badge.tournament_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Direct challenge for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct ChallengeInfo {
+    pub id: u64,
+    pub challenger: String,
+    pub opponent: String,
+    pub stake: Amount,
+    /// "native" or the token application ID
+    pub currency: String,
+    pub rules: GameRules,
+    pub room_code: String,
+    pub status: String,
+    pub game_id: Option<u64>,
+    pub created_at: u64,
+    pub responded_at: Option<u64>,
+}
+
+impl From<Challenge> for ChallengeInfo {
+    fn from(challenge: Challenge) -> Self {
+        Self {
+            id: challenge.id,
+            challenger: challenge.challenger,
+            opponent: challenge.opponent,
+            stake: challenge.stake,
+            currency: challenge.currency.to_string(),
+            rules: challenge.rules,
+            room_code: challenge.room_code,
+            status: format!("{:?}", challenge.status),
+            game_id: challenge.game_id,
+            created_at: challenge.created_at,
+            responded_at: challenge.responded_at,
+        }
+    }
+}
+
+/// Challenge issued on another chain, for GraphQL responses
+#[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedChallengeInfo {
+    /// Chain the challenge was issued on, where it is answered
+    pub origin_chain: String,
+    pub challenge: ChallengeInfo,
+}
+
 /// Games list response
 #[derive(SimpleObject, Debug, Clone, Serialize, Deserialize)]
 pub struct GamesResponse {
@@ -91,8 +837,7 @@ pub struct GamesResponse {
 }
 
 pub struct AirHockeyService {
-    state: AirHockeyState,
-    runtime: ServiceRuntime<Self>,
+    state: Arc<AirHockeyState>,
 }
 
 linera_sdk::service!(AirHockeyService);
@@ -108,12 +853,14 @@ impl Service for AirHockeyService {
         let state = AirHockeyState::load(runtime.root_view_storage_context())
             .await
             .expect("Failed to load state");
-        Self { state, runtime }
+        Self {
+            state: Arc::new(state),
+        }
     }
 
     async fn handle_query(&self, query: Self::Query) -> Self::QueryResponse {
         let schema = Schema::build(
-            QueryRoot::new(&self.state),
+            QueryRoot::new(self.state.clone()),
             EmptyMutation,
             EmptySubscription,
         )
@@ -123,28 +870,177 @@ impl Service for AirHockeyService {
     }
 }
 
-/// GraphQL query root - snapshot the data for query execution
+/// GraphQL query root - snapshot the registers, keep the state for map lookups
 pub struct QueryRoot {
     next_game_id: u64,
     owner: String,
-    total_stake_pool: u64,
+    state: Arc<AirHockeyState>,
 }
 
 impl QueryRoot {
-    fn new(state: &AirHockeyState) -> Self {
+    fn new(state: Arc<AirHockeyState>) -> Self {
         Self {
             next_game_id: *state.next_game_id.get(),
             owner: state.owner.get().clone(),
-            total_stake_pool: *state.total_stake_pool.get(),
+            state,
+        }
+    }
+
+    /// Load a season, ranking the live season stats while it is still open
+    async fn load_season(&self, id: u64) -> Option<SeasonInfo> {
+        let mut season = self
+            .state
+            .seasons
+            .get(&id)
+            .await
+            .expect("Failed to get season")?;
+
+        if season.ended_at.is_none() {
+            let mut standings = Vec::with_capacity(season.players.len());
+            for player in &season.players {
+                let stats = self
+                    .state
+                    .load_season_stats(&(id, player.clone()))
+                    .await
+                    .unwrap_or_default();
+                standings.push(SeasonStanding::new(player.clone(), &stats));
+            }
+            Season::rank(&mut standings);
+            season.standings = standings;
         }
+
+        let mut info = SeasonInfo::from(season);
+        for standing in &mut info.standings {
+            standing.nickname = self.nickname(&standing.player).await;
+        }
+        Some(info)
+    }
+
+    /// Look up a player's nickname, if they have a profile
+    async fn nickname(&self, player: &str) -> Option<String> {
+        self.state
+            .player_profiles
+            .get(player)
+            .await
+            .expect("Failed to get profile")
+            .map(|profile| profile.nickname)
+    }
+
+    /// Load games by ID, skipping any that no longer exist
+    async fn games_info(&self, ids: Vec<u64>) -> Vec<GameInfo> {
+        let mut games = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(game) = self.state.load_game(id).await {
+                games.push(self.game_info(game).await);
+            }
+        }
+        games
+    }
+
+    /// Build game info with player nicknames resolved
+    async fn game_info(&self, game: Game) -> GameInfo {
+        let mut info = GameInfo::from(game);
+        info.creator_nickname = self.nickname(&info.creator).await;
+        if let Some(opponent) = &info.opponent {
+            info.opponent_nickname = self.nickname(opponent).await;
+        }
+        info
     }
 }
 
 #[Object]
 impl QueryRoot {
-    /// Get total stake pool
-    async fn total_stake_pool(&self) -> String {
-        self.total_stake_pool.to_string()
+    /// Get the native token stake pool
+    async fn total_stake_pool(&self) -> Amount {
+        self.state
+            .stake_pools
+            .get(&StakeCurrency::Native)
+            .await
+            .expect("Failed to get stake pool")
+            .unwrap_or_default()
+    }
+
+    /// Get the stake pool of every currency staked so far
+    async fn stake_pools(&self) -> Vec<StakePoolInfo> {
+        self.state
+            .stake_pools
+            .index_values()
+            .await
+            .expect("Failed to get stake pools")
+            .into_iter()
+            .map(|(currency, amount)| StakePoolInfo {
+                currency: currency.to_string(),
+                amount,
+            })
+            .collect()
+    }
+
+    /// Get games waiting for an opponent
+    async fn open_games(&self) -> Vec<GameInfo> {
+        let ids = self
+            .state
+            .waiting_games
+            .indices()
+            .await
+            .expect("Failed to get waiting games");
+        self.games_info(ids).await
+    }
+
+    /// Get games in progress
+    async fn active_games(&self) -> Vec<GameInfo> {
+        let ids = self
+            .state
+            .active_games
+            .indices()
+            .await
+            .expect("Failed to get active games");
+        self.games_info(ids).await
+    }
+
+    /// Get the most recently completed games, newest first
+    async fn recent_games(&self) -> Vec<GameInfo> {
+        let mut ids = self.state.recent_completed_games.get().clone();
+        ids.reverse();
+        self.games_info(ids).await
+    }
+
+    /// Get the summary of a player's archived games
+    async fn player_archive(&self, player: String) -> PlayerArchiveInfo {
+        self.state
+            .player_archives
+            .get(&player)
+            .await
+            .expect("Failed to get archive")
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Get the stored schema version and record migration progress
+    async fn schema(&self) -> SchemaInfo {
+        SchemaInfo {
+            version: *self.state.schema_version.get(),
+            latest_version: SCHEMA_VERSION,
+            migration_cursor: *self.state.migration_cursor.get(),
+        }
+    }
+
+    /// Get every stake pool that differs from the stakes currently in escrow
+    async fn stake_pool_drift(&self) -> Vec<PoolDriftInfo> {
+        self.state
+            .stake_pool_drift()
+            .await
+            .into_iter()
+            .map(PoolDriftInfo::from)
+            .collect()
+    }
+
+    /// Get the result of the last owner reconciliation
+    async fn last_reconciliation(&self) -> Option<ReconciliationInfo> {
+        self.state
+            .last_reconciliation
+            .get()
+            .clone()
+            .map(ReconciliationInfo::from)
     }
 
     /// Get next game ID (useful for UI)
@@ -156,4 +1052,406 @@ impl QueryRoot {
     async fn owner(&self) -> String {
         self.owner.clone()
     }
+
+    /// Get a game by ID
+    async fn game(&self, id: u64) -> Option<GameInfo> {
+        let game = self.state.load_game(id).await?;
+        Some(self.game_info(game).await)
+    }
+
+    /// Get the result submitted for an active game, if it awaits confirmation
+    async fn pending_result(&self, game_id: u64) -> Option<PendingResultInfo> {
+        self.state
+            .pending_results
+            .get(&game_id)
+            .await
+            .expect("Failed to get pending result")
+            .map(|pending| PendingResultInfo {
+                game_id,
+                player1_score: pending.player1_score,
+                player2_score: pending.player2_score,
+                confirmation_deadline: pending.confirmation_deadline(),
+                submitted_by: pending.submitted_by,
+                submitted_at: pending.submitted_at,
+                replay_hash: pending.replay_hash,
+                timeline: pending.timeline,
+            })
+    }
+
+    /// Get the spectator side pool of a game, if anyone bet on it
+    async fn side_pool(&self, game_id: u64) -> Option<SidePoolInfo> {
+        self.state
+            .side_pools
+            .get(&game_id)
+            .await
+            .expect("Failed to get side pool")
+            .map(SidePoolInfo::from)
+    }
+
+    /// Get the sponsor bonus of a game, if it has one
+    async fn game_sponsorship(&self, game_id: u64) -> Option<SponsorshipInfo> {
+        self.state
+            .game_sponsorships
+            .get(&game_id)
+            .await
+            .expect("Failed to get sponsorship")
+            .map(SponsorshipInfo::from)
+    }
+
+    /// Get the goal-by-goal timeline of a game, if one was recorded
+    async fn goal_timeline(&self, game_id: u64) -> Option<Vec<GoalEvent>> {
+        self.state
+            .goal_timelines
+            .get(&game_id)
+            .await
+            .expect("Failed to get timeline")
+    }
+
+    /// Get a best-of-N series with its games and aggregate score
+    async fn series(&self, id: u64) -> Option<SeriesInfo> {
+        let series = self
+            .state
+            .series
+            .get(&id)
+            .await
+            .expect("Failed to get series")?;
+
+        let games = self.games_info(series.game_ids.clone()).await;
+        Some(SeriesInfo::from(series).with_games(games))
+    }
+
+    /// Get a tournament with its bracket and the scores of played games
+    async fn tournament(&self, id: u64) -> Option<TournamentInfo> {
+        let tournament = self
+            .state
+            .tournaments
+            .get(&id)
+            .await
+            .expect("Failed to get tournament")?;
+
+        let mut info = TournamentInfo::from(tournament);
+        for bracket_match in info.rounds.iter_mut().flatten() {
+            let Some(game_id) = bracket_match.game_id else {
+                continue;
+            };
+            if let Some(game) = self.state.load_game(game_id).await {
+                if game.status == GameStatus::Completed {
+                    bracket_match.player1_score = Some(game.player1_score);
+                    bracket_match.player2_score = Some(game.player2_score);
+                }
+            }
+        }
+
+        Some(info)
+    }
+
+    /// Get the sponsor bonus of a tournament, if it has one
+    async fn tournament_sponsorship(&self, tournament_id: u64) -> Option<SponsorshipInfo> {
+        self.state
+            .tournament_sponsorships
+            .get(&tournament_id)
+            .await
+            .expect("Failed to get sponsorship")
+            .map(SponsorshipInfo::from)
+    }
+
+    /// Get lifetime stats for a player
+    async fn player_stats(&self, player: String) -> PlayerStatsInfo {
+        self.state
+            .load_player_stats(&player)
+            .await
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Get stats for a player in a given season
+    async fn season_stats(&self, season_id: u64, player: String) -> PlayerStatsInfo {
+        self.state
+            .load_season_stats(&(season_id, player))
+            .await
+            .unwrap_or_default()
+            .into()
+    }
+
+    /// Get the top `limit` players by longest win streak, then current
+    /// streak; at most `MAX_LEADERBOARD_ENTRIES`
+    async fn streak_leaderboard(&self, limit: u32) -> Vec<StreakEntryInfo> {
+        // Unmigrated players count too, with the streaks their badges seed
+        let mut players = BTreeSet::new();
+        players.extend(
+            self.state
+                .player_stats
+                .indices()
+                .await
+                .expect("Failed to get stats"),
+        );
+        players.extend(
+            self.state
+                .legacy_player_stats
+                .indices()
+                .await
+                .expect("Failed to get stats"),
+        );
+
+        let mut entries = Vec::new();
+        for player in players {
+            let Some(stats) = self.state.load_player_stats(&player).await else {
+                continue;
+            };
+            if stats.longest_win_streak > 0 {
+                entries.push((player, stats.longest_win_streak, stats.current_streak));
+            }
+        }
+
+        entries.sort_by(|a, b| b.1.cmp(&a.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+        entries.truncate(limit.min(MAX_LEADERBOARD_ENTRIES) as usize);
+
+        let mut leaderboard = Vec::with_capacity(entries.len());
+        for (index, (player, longest_win_streak, current_streak)) in entries.into_iter().enumerate()
+        {
+            leaderboard.push(StreakEntryInfo {
+                rank: index as u32 + 1,
+                nickname: self.nickname(&player).await,
+                player,
+                longest_win_streak,
+                current_streak,
+            });
+        }
+        leaderboard
+    }
+
+    /// Get the native season and quest rewards a player received
+    async fn reward_totals(&self, player: String) -> RewardTotalsInfo {
+        let totals = self
+            .state
+            .reward_totals
+            .get(&player)
+            .await
+            .expect("Failed to get rewards")
+            .unwrap_or_default();
+        RewardTotalsInfo::from(totals)
+    }
+
+    /// Get the season currently open, if any
+    async fn current_season(&self) -> Option<SeasonInfo> {
+        let season_id = (*self.state.current_season.get())?;
+        self.load_season(season_id).await
+    }
+
+    /// Get a season with its standings
+    async fn season(&self, id: u64) -> Option<SeasonInfo> {
+        self.load_season(id).await
+    }
+
+    /// Get a player's profile
+    async fn profile(&self, player: String) -> Option<PlayerProfileInfo> {
+        self.state
+            .player_profiles
+            .get(&player)
+            .await
+            .expect("Failed to get profile")
+            .map(PlayerProfileInfo::from)
+    }
+
+    /// Get the achievement badges a player has earned
+    async fn badges(&self, player: String) -> PlayerBadgesInfo {
+        let badges = self
+            .state
+            .player_badges
+            .get(&player)
+            .await
+            .expect("Failed to get badges")
+            .unwrap_or_default();
+        PlayerBadgesInfo::from(badges)
+    }
+
+    /// Find the player using a nickname (case-insensitive)
+    async fn player_by_nickname(&self, nickname: String) -> Option<String> {
+        self.state
+            .nicknames
+            .get(&PlayerProfile::nickname_key(&nickname))
+            .await
+            .expect("Failed to get nickname")
+    }
+
+    /// Get a player's friends list
+    async fn friends(&self, player: String) -> Vec<String> {
+        self.state
+            .friends
+            .get(&player)
+            .await
+            .expect("Failed to get friends")
+            .unwrap_or_default()
+    }
+
+    /// Get the players a player has blocked
+    async fn blocked_players(&self, player: String) -> Vec<String> {
+        self.state
+            .blocked_players
+            .get(&player)
+            .await
+            .expect("Failed to get blocklist")
+            .unwrap_or_default()
+    }
+
+    /// Get a challenge by ID
+    async fn challenge(&self, id: u64) -> Option<ChallengeInfo> {
+        self.state
+            .challenges
+            .get(&id)
+            .await
+            .expect("Failed to get challenge")
+            .map(ChallengeInfo::from)
+    }
+
+    /// Get a quest
+    async fn quest(&self, id: u64) -> Option<QuestInfo> {
+        self.state
+            .quests
+            .get(&id)
+            .await
+            .expect("Failed to get quest")
+            .map(QuestInfo::from)
+    }
+
+    /// Get the quests that settled games currently count toward
+    async fn active_quests(&self) -> Vec<QuestInfo> {
+        let mut quests = Vec::new();
+        for id in self.state.active_quests.get() {
+            if let Some(quest) = self
+                .state
+                .quests
+                .get(id)
+                .await
+                .expect("Failed to get quest")
+            {
+                quests.push(QuestInfo::from(quest));
+            }
+        }
+        quests
+    }
+
+    /// Get a player's recorded progress on every active quest
+    async fn quest_progress(&self, player: String) -> Vec<QuestProgressInfo> {
+        let mut result = Vec::new();
+        for id in self.state.active_quests.get() {
+            let Some(quest) = self
+                .state
+                .quests
+                .get(id)
+                .await
+                .expect("Failed to get quest")
+            else {
+                continue;
+            };
+            let progress = self
+                .state
+                .quest_progress
+                .get(&(*id, player.clone()))
+                .await
+                .expect("Failed to get quest progress")
+                .unwrap_or_default();
+            result.push(QuestProgressInfo {
+                period: progress.period,
+                completed: progress.progress >= quest.goal.target(),
+                progress: progress.progress,
+                claimed: progress.claimed,
+                claimable: progress.claimable(quest.goal.target()),
+                quest: QuestInfo::from(quest),
+            });
+        }
+        result
+    }
+
+    /// Get pending challenges a player issued or received
+    async fn pending_challenges(&self, player: String) -> Vec<ChallengeInfo> {
+        let ids = self
+            .state
+            .pending_challenges
+            .get(&player)
+            .await
+            .expect("Failed to get challenges")
+            .unwrap_or_default();
+
+        let mut challenges = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(challenge) = self
+                .state
+                .challenges
+                .get(&id)
+                .await
+                .expect("Failed to get challenge")
+            {
+                challenges.push(challenge.into());
+            }
+        }
+        challenges
+    }
+
+    /// Get challenges other chains sent to a player
+    async fn received_challenges(&self, player: String) -> Vec<ReceivedChallengeInfo> {
+        self.state
+            .received_challenges
+            .get(&player)
+            .await
+            .expect("Failed to get challenges")
+            .unwrap_or_default()
+            .into_iter()
+            .map(|received| ReceivedChallengeInfo {
+                origin_chain: received.origin_chain.to_string(),
+                challenge: received.challenge.into(),
+            })
+            .collect()
+    }
+
+    /// Get the record between two players, from player A's point of view
+    async fn head_to_head(&self, player_a: String, player_b: String) -> HeadToHeadInfo {
+        let key = pair_key(&player_a, &player_b);
+        let record = self
+            .state
+            .head_to_head
+            .get(&key)
+            .await
+            .expect("Failed to get head-to-head")
+            .unwrap_or_default();
+
+        if key.0 == player_a {
+            return (key, record).into();
+        }
+        let flipped = HeadToHead {
+            first_wins: record.second_wins,
+            second_wins: record.first_wins,
+            tokens_won: [record.tokens_won[1], record.tokens_won[0]],
+            ..record
+        };
+        ((key.1, key.0), flipped).into()
+    }
+
+    /// Get the current anti-collusion limits
+    async fn collusion_limits(&self) -> CollusionLimits {
+        *self.state.collusion_limits.get()
+    }
+
+    /// Get every pair of players flagged for repeated games, for owner review
+    async fn flagged_pairs(&self) -> Vec<PairActivityInfo> {
+        let pairs = self
+            .state
+            .flagged_pairs
+            .indices()
+            .await
+            .expect("Failed to get flagged pairs");
+
+        let mut flagged = Vec::with_capacity(pairs.len());
+        for pair in pairs {
+            let activity = self
+                .state
+                .pair_activity
+                .get(&pair)
+                .await
+                .expect("Failed to get pair activity")
+                .unwrap_or_default();
+            flagged.push((pair, activity).into());
+        }
+        flagged
+    }
 }
@@ -2,7 +2,14 @@
 //!
 //! This module defines the on-chain state for staked multiplayer games.
 
-use linera_sdk::views::{linera_views, MapView, RegisterView, RootView, ViewStorageContext};
+use std::collections::BTreeMap;
+
+use air_hockey::{BetOutcome, CollusionLimits, GameRules, GoalEvent, QuestGoal, StakeCurrency};
+use air_hockey_physics::InputFrame;
+use linera_sdk::{
+    linera_base_types::{Amount, BcsHashable, ChainId, CryptoHash},
+    views::{linera_views, MapView, RegisterView, RootView, SetView, ViewStorageContext},
+};
 use serde::{Deserialize, Serialize};
 
 /// Game status on the blockchain
@@ -15,8 +22,51 @@ pub enum GameStatus {
     Active,
     /// Game completed, winner determined
     Completed,
-    /// Game cancelled before completion
+    /// Game cancelled before completion, or voided
     Cancelled,
+    /// Result disputed, waiting for both input logs to be replayed
+    Disputed,
+}
+
+/// How long players have to commit and reveal input logs after a dispute (1 hour)
+pub const DISPUTE_WINDOW_MICROS: u64 = 60 * 60 * 1_000_000;
+
+/// How long the other player has to dispute a submitted result (15 minutes)
+pub const RESULT_CONFIRMATION_WINDOW_MICROS: u64 = 15 * 60 * 1_000_000;
+
+/// A result submitted by one player of an active game; it settles when the
+/// other player submits the same score, or when the confirmation window
+/// passes without a dispute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingResult {
+    pub player1_score: u8,
+    pub player2_score: u8,
+    /// Participant who submitted the result
+    pub submitted_by: String,
+    /// Block timestamp when the result was submitted
+    pub submitted_at: u64,
+    /// Hash of the off-chain match replay, if submitted
+    pub replay_hash: Option<CryptoHash>,
+    /// Goal-by-goal timeline, if submitted
+    pub timeline: Option<Vec<GoalEvent>>,
+}
+
+impl PendingResult {
+    /// Time after which the result can no longer be disputed and can be finalized
+    pub fn confirmation_deadline(&self) -> u64 {
+        self.submitted_at + RESULT_CONFIRMATION_WINDOW_MICROS
+    }
+}
+
+/// Score reached by replaying the revealed input logs of a disputed game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayVerdict {
+    pub player1_score: u8,
+    pub player2_score: u8,
+    /// Ticks simulated
+    pub ticks: u32,
+    /// Whether the replayed score was settled (false means the game was voided)
+    pub settled: bool,
 }
 
 /// A single game record
@@ -28,8 +78,10 @@ pub struct Game {
     pub creator: String,
     /// Address of opponent (player 2), None until joined
     pub opponent: Option<String>,
-    /// Stake amount in smallest token unit
-    pub stake: u64,
+    /// Stake amount per player
+    pub stake: Amount,
+    /// Token the stake is denominated in
+    pub currency: StakeCurrency,
     /// Current game status
     pub status: GameStatus,
     /// Winner address (None if not yet determined or draw)
@@ -46,16 +98,35 @@ pub struct Game {
     pub player2_score: u8,
     /// WebSocket room code for real-time gameplay
     pub room_code: String,
+    /// Series this game belongs to (None for standalone games)
+    pub series_id: Option<u64>,
+    /// Tournament this game belongs to (None for standalone games)
+    pub tournament_id: Option<u64>,
+    /// Scoring rules the game is played under
+    pub rules: GameRules,
+    /// Participant who disputed the game, if any
+    pub disputed_by: Option<String>,
+    /// Block timestamp when the dispute was opened
+    pub disputed_at: Option<u64>,
+    /// Committed input log hashes for player 1 and player 2
+    pub input_commitments: [Option<CryptoHash>; 2],
+    /// Outcome of replaying the revealed input logs
+    pub replay_verdict: Option<ReplayVerdict>,
+    /// Minimum reliability (per mille) required to join
+    pub min_reliability: Option<u16>,
+    /// Hash of the off-chain match replay submitted with the result
+    pub replay_hash: Option<CryptoHash>,
 }
 
 impl Game {
     /// Create a new game
-    pub fn new(id: u64, creator: String, stake: u64, room_code: String, timestamp: u64) -> Self {
+    pub fn new(id: u64, creator: String, stake: Amount, room_code: String, timestamp: u64) -> Self {
         Self {
             id,
             creator,
             opponent: None,
             stake,
+            currency: StakeCurrency::Native,
             status: GameStatus::Waiting,
             winner: None,
             created_at: timestamp,
@@ -64,6 +135,15 @@ impl Game {
             player1_score: 0,
             player2_score: 0,
             room_code,
+            series_id: None,
+            tournament_id: None,
+            rules: GameRules::default(),
+            disputed_by: None,
+            disputed_at: None,
+            input_commitments: [None, None],
+            replay_verdict: None,
+            min_reliability: None,
+            replay_hash: None,
         }
     }
 
@@ -78,13 +158,275 @@ impl Game {
     }
 
     /// Get total pot (both stakes)
-    pub fn total_pot(&self) -> u64 {
-        self.stake * 2
+    pub fn total_pot(&self) -> Amount {
+        self.stake.try_mul(2).expect("Pot overflow")
+    }
+
+    /// Deadline for committing and revealing input logs of a disputed game
+    pub fn dispute_deadline(&self) -> Option<u64> {
+        self.disputed_at
+            .map(|disputed_at| disputed_at + DISPUTE_WINDOW_MICROS)
+    }
+
+    /// Player slot of a participant: 0 for the creator, 1 for the opponent
+    pub fn player_index(&self, player: &str) -> Option<usize> {
+        if self.creator == player {
+            Some(0)
+        } else if self.opponent.as_deref() == Some(player) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+/// A best-of-N series of games between the same two players
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Series {
+    /// Unique series identifier
+    pub id: u64,
+    /// Address of series creator (player 1 in every game)
+    pub creator: String,
+    /// Address of opponent (player 2 in every game), None until joined
+    pub opponent: Option<String>,
+    /// Stake amount per player, escrowed once for the whole series
+    pub stake: Amount,
+    /// Token the stake is denominated in
+    pub currency: StakeCurrency,
+    /// Maximum number of decided games (odd)
+    pub best_of: u8,
+    /// Current series status
+    pub status: GameStatus,
+    /// Winner address (None until a player clinches the majority)
+    pub winner: Option<String>,
+    /// Games played in this series, in order
+    pub game_ids: Vec<u64>,
+    /// Games won by player 1
+    pub player1_wins: u8,
+    /// Games won by player 2
+    pub player2_wins: u8,
+    /// Block timestamp when series was created
+    pub created_at: u64,
+    /// Block timestamp when opponent joined
+    pub started_at: Option<u64>,
+    /// Block timestamp when series ended
+    pub ended_at: Option<u64>,
+    /// WebSocket room code shared by all games of the series
+    pub room_code: String,
+}
+
+impl Series {
+    /// Create a new series
+    pub fn new(
+        id: u64,
+        creator: String,
+        stake: Amount,
+        best_of: u8,
+        room_code: String,
+        timestamp: u64,
+    ) -> Self {
+        Self {
+            id,
+            creator,
+            opponent: None,
+            stake,
+            currency: StakeCurrency::Native,
+            best_of,
+            status: GameStatus::Waiting,
+            winner: None,
+            game_ids: Vec::new(),
+            player1_wins: 0,
+            player2_wins: 0,
+            created_at: timestamp,
+            started_at: None,
+            ended_at: None,
+            room_code,
+        }
+    }
+
+    /// Check if series can be joined
+    pub fn can_join(&self) -> bool {
+        self.status == GameStatus::Waiting && self.opponent.is_none()
+    }
+
+    /// Number of game wins needed to clinch the series
+    pub fn wins_needed(&self) -> u8 {
+        self.best_of / 2 + 1
+    }
+
+    /// Get total pot (both stakes)
+    pub fn total_pot(&self) -> Amount {
+        self.stake.try_mul(2).expect("Pot overflow")
+    }
+}
+
+/// One pairing in a tournament bracket
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BracketMatch {
+    /// Upper slot, None until the feeding match is decided
+    pub player1: Option<String>,
+    /// Lower slot, None until the feeding match is decided
+    pub player2: Option<String>,
+    /// Game played for this pairing, None until both slots are filled or
+    /// when one player had blocked the other and the pairing was a walkover
+    pub game_id: Option<u64>,
+    /// Player advancing from this pairing
+    pub winner: Option<String>,
+}
+
+/// A single-elimination tournament
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Tournament {
+    /// Unique tournament identifier
+    pub id: u64,
+    /// Address of tournament organizer
+    pub organizer: String,
+    /// Entry fee per player
+    pub entry_fee: Amount,
+    /// Token entry fees and prizes are denominated in
+    pub currency: StakeCurrency,
+    /// Number of players (power of two)
+    pub size: u8,
+    /// Percentage of the prize pool for each finishing place, best first;
+    /// losers of the same round split the places they tie for
+    pub prize_split: Vec<u8>,
+    /// Current tournament status (Waiting while registration is open)
+    pub status: GameStatus,
+    /// Registered players in registration order
+    pub players: Vec<String>,
+    /// Bracket rounds, first round first; each round halves the matches
+    pub rounds: Vec<Vec<BracketMatch>>,
+    /// Tournament winner
+    pub champion: Option<String>,
+    /// Prizes paid out at the end, in finishing order
+    pub payouts: Vec<(String, Amount)>,
+    /// Block timestamp when tournament was created
+    pub created_at: u64,
+    /// Block timestamp when the bracket was seeded
+    pub started_at: Option<u64>,
+    /// Block timestamp when tournament ended
+    pub ended_at: Option<u64>,
+}
+
+impl Tournament {
+    /// Create a new tournament
+    pub fn new(
+        id: u64,
+        organizer: String,
+        entry_fee: Amount,
+        size: u8,
+        prize_split: Vec<u8>,
+        timestamp: u64,
+    ) -> Self {
+        Self {
+            id,
+            organizer,
+            entry_fee,
+            currency: StakeCurrency::Native,
+            size,
+            prize_split,
+            status: GameStatus::Waiting,
+            players: Vec::new(),
+            rounds: Vec::new(),
+            champion: None,
+            payouts: Vec::new(),
+            created_at: timestamp,
+            started_at: None,
+            ended_at: None,
+        }
+    }
+
+    /// Check if a player can still register
+    pub fn can_register(&self, player: &str) -> bool {
+        self.status == GameStatus::Waiting
+            && self.players.len() < usize::from(self.size)
+            && !self.players.iter().any(|p| p == player)
+    }
+
+    /// Check if every slot is taken
+    pub fn is_full(&self) -> bool {
+        self.players.len() == usize::from(self.size)
+    }
+
+    /// Get total prize pool (all entry fees)
+    pub fn prize_pool(&self) -> Amount {
+        self.entry_fee
+            .try_mul(self.players.len() as u128)
+            .expect("Prize pool overflow")
+    }
+
+    /// Bracket slot order for a power-of-two field: seed 1 meets seed N, and
+    /// the top two seeds can only meet in the final
+    pub fn seed_order(size: usize) -> Vec<usize> {
+        let mut order = vec![1];
+        while order.len() < size {
+            let next_len = order.len() * 2;
+            order = order
+                .iter()
+                .flat_map(|&seed| [seed, next_len + 1 - seed])
+                .collect();
+        }
+        order
+    }
+
+    /// Locate the bracket match played as the given game
+    pub fn find_match(&self, game_id: u64) -> Option<(usize, usize)> {
+        self.rounds.iter().enumerate().find_map(|(round, matches)| {
+            matches
+                .iter()
+                .position(|m| m.game_id == Some(game_id))
+                .map(|index| (round, index))
+        })
+    }
+}
+
+/// Rating assigned to players with no rated games
+pub const INITIAL_RATING: u32 = 1200;
+
+/// Maximum rating change per game
+pub const RATING_K_FACTOR: i64 = 32;
+
+/// Tokens a player has won and lost in one currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct TokenTotals {
+    pub currency: StakeCurrency,
+    pub won: Amount,
+    pub lost: Amount,
+}
+
+/// Number of results kept in `RecentResults`
+pub const RECENT_RESULTS: usize = 10;
+
+/// Result of a game from one player's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// Last `RECENT_RESULTS` results of a player, kept in a ring buffer
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RecentResults {
+    pub slots: Vec<GameResult>,
+    /// Slot the next result goes to; the oldest result once the buffer is full
+    pub next: usize,
+}
+
+impl RecentResults {
+    /// Add a result, overwriting the oldest one once the buffer is full
+    pub fn push(&mut self, result: GameResult) {
+        if self.slots.len() < RECENT_RESULTS {
+            self.slots.push(result);
+        } else {
+            self.slots[self.next] = result;
+        }
+        self.next = (self.next + 1) % RECENT_RESULTS;
     }
 }
 
 /// Player statistics
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerStats {
     /// Total games played
     pub games_played: u64,
@@ -92,24 +434,1541 @@ pub struct PlayerStats {
     pub wins: u64,
     /// Games lost
     pub losses: u64,
-    /// Total tokens won
+    /// Tokens won and lost, per currency
+    pub tokens: Vec<TokenTotals>,
+    /// Elo-style skill rating
+    pub rating: u32,
+    /// Games conceded or abandoned during a dispute
+    pub forfeits: u64,
+    /// Active games voided because no result arrived in time
+    pub expired_games: u64,
+    /// Tournaments cancelled while other players were registered
+    pub late_cancellations: u64,
+    /// Disputes opened and then lost on replay
+    pub lost_disputes: u64,
+    /// Run of identical ranked results: positive for wins, negative for
+    /// losses, zero after a draw
+    pub current_streak: i32,
+    /// Longest run of ranked wins; for stats from before streak tracking
+    /// this is a lower bound, seeded from the run still going at the upgrade
+    pub longest_win_streak: u32,
+    /// Most recent ranked results
+    pub recent_results: RecentResults,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            games_played: 0,
+            wins: 0,
+            losses: 0,
+            tokens: Vec::new(),
+            rating: INITIAL_RATING,
+            forfeits: 0,
+            expired_games: 0,
+            late_cancellations: 0,
+            lost_disputes: 0,
+            current_streak: 0,
+            longest_win_streak: 0,
+            recent_results: RecentResults::default(),
+        }
+    }
+}
+
+impl PlayerStats {
+    /// Rating change against an opponent, given the actual score in
+    /// thousandths (1000 win, 500 draw, 0 loss)
+    ///
+    /// Uses a linear approximation of the Elo expectation so the contract
+    /// stays in integer arithmetic.
+    pub fn rating_change(&self, opponent_rating: u32, score_milli: i64) -> i64 {
+        let diff = i64::from(self.rating) - i64::from(opponent_rating);
+        let expected_milli = (500 + diff * 1000 / 800).clamp(50, 950);
+        RATING_K_FACTOR * (score_milli - expected_milli) / 1000
+    }
+
+    /// Share of commitments honoured, in per mille; players with no history score 1000
+    pub fn reliability(&self) -> u32 {
+        // Forfeits and lost disputes already count as played games
+        let commitments = self.games_played + self.expired_games + self.late_cancellations;
+        if commitments == 0 {
+            return 1000;
+        }
+        let incidents =
+            self.forfeits + self.expired_games + self.late_cancellations + self.lost_disputes;
+        (commitments.saturating_sub(incidents) * 1000 / commitments) as u32
+    }
+
+    /// Add token winnings and losses in a currency
+    pub fn credit_tokens(&mut self, currency: StakeCurrency, won: Amount, lost: Amount) {
+        let index = match self
+            .tokens
+            .iter()
+            .position(|totals| totals.currency == currency)
+        {
+            Some(index) => index,
+            None => {
+                self.tokens.push(TokenTotals {
+                    currency,
+                    ..TokenTotals::default()
+                });
+                self.tokens.len() - 1
+            }
+        };
+        let totals = &mut self.tokens[index];
+        totals
+            .won
+            .try_add_assign(won)
+            .expect("Token totals overflow");
+        totals
+            .lost
+            .try_add_assign(lost)
+            .expect("Token totals overflow");
+    }
+
+    /// Extend the streaks and recent results with a ranked game
+    pub fn record_result(&mut self, result: GameResult) {
+        self.current_streak = match result {
+            GameResult::Win => self.current_streak.max(0).saturating_add(1),
+            GameResult::Loss => self.current_streak.min(0).saturating_sub(1),
+            GameResult::Draw => 0,
+        };
+        if result == GameResult::Win {
+            self.longest_win_streak = self
+                .longest_win_streak
+                .max(self.current_streak.unsigned_abs());
+        }
+        self.recent_results.push(result);
+    }
+
+    /// Apply a rating change, never dropping below zero
+    pub fn apply_rating_change(&mut self, change: i64) {
+        self.rating = (i64::from(self.rating) + change).max(0) as u32;
+    }
+
+    /// Record a completed game for both of its players; unranked games only
+    /// count as played
+    pub fn record_game(
+        creator_stats: &mut Self,
+        opponent_stats: &mut Self,
+        game: &Game,
+        ranked: bool,
+    ) {
+        // Rating changes are computed from the pre-game ratings of both players
+        let creator_score = if game.winner.as_ref() == Some(&game.creator) {
+            1000
+        } else if game.winner.is_some() {
+            0
+        } else {
+            500
+        };
+        let creator_change = creator_stats.rating_change(opponent_stats.rating, creator_score);
+        let opponent_change =
+            opponent_stats.rating_change(creator_stats.rating, 1000 - creator_score);
+
+        creator_stats.games_played += 1;
+        opponent_stats.games_played += 1;
+
+        if !ranked {
+            return;
+        }
+
+        if game.winner.as_ref() == Some(&game.creator) {
+            creator_stats.wins += 1;
+            creator_stats.credit_tokens(game.currency, game.stake, Amount::ZERO);
+            creator_stats.record_result(GameResult::Win);
+            opponent_stats.losses += 1;
+            opponent_stats.credit_tokens(game.currency, Amount::ZERO, game.stake);
+            opponent_stats.record_result(GameResult::Loss);
+        } else if game.winner.is_some() {
+            opponent_stats.wins += 1;
+            opponent_stats.credit_tokens(game.currency, game.stake, Amount::ZERO);
+            opponent_stats.record_result(GameResult::Win);
+            creator_stats.losses += 1;
+            creator_stats.credit_tokens(game.currency, Amount::ZERO, game.stake);
+            creator_stats.record_result(GameResult::Loss);
+        } else {
+            creator_stats.record_result(GameResult::Draw);
+            opponent_stats.record_result(GameResult::Draw);
+        }
+
+        creator_stats.apply_rating_change(creator_change);
+        opponent_stats.apply_rating_change(opponent_change);
+    }
+}
+
+/// Map key for a pair of players, independent of argument order
+pub fn pair_key(player_a: &str, player_b: &str) -> (String, String) {
+    if player_a <= player_b {
+        (player_a.to_string(), player_b.to_string())
+    } else {
+        (player_b.to_string(), player_a.to_string())
+    }
+}
+
+/// Recent games between two players, keyed by `pair_key`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PairActivity {
+    /// Block timestamp when the current window started
+    pub window_started_at: u64,
+    /// Games played in the current window
+    pub games: u32,
+    /// Native tokens won by the first and second player of the pair in
+    /// the current window
+    pub tokens_won: [Amount; 2],
+    /// Block timestamp when the pair last went over the limits
+    pub flagged_at: Option<u64>,
+}
+
+impl PairActivity {
+    /// Check if the current window is over the limits
+    pub fn exceeds(&self, limits: &CollusionLimits) -> bool {
+        self.games > limits.max_games
+            || self.tokens_won[0].abs_diff(self.tokens_won[1]) > limits.max_net_tokens
+    }
+
+    /// Count a game in which the player in slot `winner` of the pair won
+    /// `native_stake`; returns whether the game still counts toward ratings
+    /// and leaderboards
+    pub fn record(
+        &mut self,
+        winner: Option<usize>,
+        native_stake: Amount,
+        timestamp: u64,
+        limits: &CollusionLimits,
+    ) -> bool {
+        self.roll_window(timestamp, limits);
+        self.games += 1;
+
+        self.record_tokens(winner, native_stake, timestamp, limits)
+    }
+
+    /// Count `native_stake` won by the player in slot `winner` without a
+    /// game, as when a series settles; returns whether the pair is still
+    /// within the limits
+    pub fn record_tokens(
+        &mut self,
+        winner: Option<usize>,
+        native_stake: Amount,
+        timestamp: u64,
+        limits: &CollusionLimits,
+    ) -> bool {
+        self.roll_window(timestamp, limits);
+
+        if let Some(winner) = winner {
+            self.tokens_won[winner].saturating_add_assign(native_stake);
+        }
+
+        if self.exceeds(limits) {
+            self.flagged_at = Some(timestamp);
+            return false;
+        }
+        true
+    }
+
+    /// Check if the pair is over the limits in a window still running at `timestamp`
+    pub fn is_flagged(&self, timestamp: u64, limits: &CollusionLimits) -> bool {
+        self.is_current(timestamp, limits) && self.exceeds(limits)
+    }
+
+    /// Start a new window if the current one is over at `timestamp`
+    fn roll_window(&mut self, timestamp: u64, limits: &CollusionLimits) {
+        if !self.is_current(timestamp, limits) {
+            self.window_started_at = timestamp;
+            self.games = 0;
+            self.tokens_won = [Amount::ZERO; 2];
+        }
+    }
+
+    /// Check if the current window is still running at `timestamp`
+    fn is_current(&self, timestamp: u64, limits: &CollusionLimits) -> bool {
+        let window_micros = limits.window_secs.saturating_mul(1_000_000);
+        timestamp < self.window_started_at.saturating_add(window_micros)
+    }
+}
+
+/// Number of recent game IDs kept for each pair of players
+pub const HEAD_TO_HEAD_RECENT_GAMES: usize = 10;
+
+/// Lifetime record between two players, keyed by `pair_key`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct HeadToHead {
+    /// Completed games between the pair
+    pub games_played: u64,
+    /// Wins by the first player of the pair
+    pub first_wins: u64,
+    /// Wins by the second player of the pair
+    pub second_wins: u64,
+    /// Drawn games
+    pub draws: u64,
+    /// Native tokens won by the first and second player of the pair
+    pub tokens_won: [Amount; 2],
+    /// Most recent game IDs, oldest first
+    pub recent_game_ids: Vec<u64>,
+}
+
+impl HeadToHead {
+    /// Record a completed game won by the player in slot `winner` of the pair
+    pub fn record(&mut self, game: &Game, winner: Option<usize>, native_stake: Amount) {
+        self.games_played += 1;
+        match winner {
+            Some(0) => self.first_wins += 1,
+            Some(_) => self.second_wins += 1,
+            None => self.draws += 1,
+        }
+        if let Some(winner) = winner {
+            self.tokens_won[winner].saturating_add_assign(native_stake);
+        }
+
+        self.recent_game_ids.push(game.id);
+        if self.recent_game_ids.len() > HEAD_TO_HEAD_RECENT_GAMES {
+            self.recent_game_ids.remove(0);
+        }
+    }
+}
+
+/// Native token rewards a player received outside of games, kept apart
+/// from the stake winnings in `PlayerStats`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct RewardTotals {
+    /// Shares of season reward pools
+    pub season_rewards: Amount,
+    /// Quest completion rewards
+    pub quest_rewards: Amount,
+}
+
+/// A player's final position in a season
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SeasonStanding {
+    /// Player address
+    pub player: String,
+    /// Season rating at close
+    pub rating: u32,
+    /// Season games played
+    pub games_played: u64,
+    /// Season wins
+    pub wins: u64,
+    /// Season losses
+    pub losses: u64,
+    /// Share of the season reward pool paid out
+    pub reward: Amount,
+}
+
+impl SeasonStanding {
+    /// Build a standing from a player's season stats
+    pub fn new(player: String, stats: &PlayerStats) -> Self {
+        Self {
+            player,
+            rating: stats.rating,
+            games_played: stats.games_played,
+            wins: stats.wins,
+            losses: stats.losses,
+            reward: Amount::ZERO,
+        }
+    }
+}
+
+/// A ranked season; stats and ratings restart at the beginning of each season
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Season {
+    /// Unique season identifier
+    pub id: u64,
+    /// Reward pool distributed at close
+    pub reward_pool: Amount,
+    /// Number of top players sharing the reward pool
+    pub reward_top_n: u8,
+    /// Players with at least one game this season
+    pub players: Vec<String>,
+    /// Final standings, filled in when the season closes
+    pub standings: Vec<SeasonStanding>,
+    /// Block timestamp when season opened
+    pub started_at: u64,
+    /// Block timestamp when season closed, None while it is running
+    pub ended_at: Option<u64>,
+}
+
+impl Season {
+    /// Open a new season
+    pub fn new(id: u64, reward_pool: Amount, reward_top_n: u8, timestamp: u64) -> Self {
+        Self {
+            id,
+            reward_pool,
+            reward_top_n,
+            players: Vec::new(),
+            standings: Vec::new(),
+            started_at: timestamp,
+            ended_at: None,
+        }
+    }
+
+    /// Order standings by rating, then wins, then fewest games played
+    pub fn rank(standings: &mut [SeasonStanding]) {
+        standings.sort_by(|a, b| {
+            b.rating
+                .cmp(&a.rating)
+                .then(b.wins.cmp(&a.wins))
+                .then(a.games_played.cmp(&b.games_played))
+        });
+    }
+}
+
+/// An owner-defined quest with a native token reward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quest {
+    /// Unique quest identifier
+    pub id: u64,
+    pub name: String,
+    pub goal: QuestGoal,
+    /// Native tokens paid for each completion
+    pub reward: Amount,
+    /// Native tokens left to pay out
+    pub reward_pool: Amount,
+    /// Period length in seconds; progress and claims reset each period.
+    /// None for a one-off quest
+    pub period_secs: Option<u64>,
+    /// Block timestamp when the quest was defined
+    pub starts_at: u64,
+    /// Block timestamp after which games no longer count
+    pub ends_at: Option<u64>,
+    /// Number of rewards claimed
+    pub claims: u32,
+}
+
+impl Quest {
+    /// Index of the period a timestamp falls in; always 0 for one-off quests
+    pub fn period(&self, timestamp: u64) -> u64 {
+        self.period_secs.map_or(0, |secs| {
+            timestamp.saturating_sub(self.starts_at) / secs.saturating_mul(1_000_000)
+        })
+    }
+
+    /// Check games at a timestamp still count toward the quest
+    pub fn is_running(&self, timestamp: u64) -> bool {
+        self.ends_at.is_none_or(|end| timestamp < end)
+    }
+}
+
+/// A player's progress on a quest within one period
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct QuestProgress {
+    /// Period the progress belongs to
+    pub period: u64,
+    pub progress: u32,
+    /// Whether the reward for this period was claimed
+    pub claimed: bool,
+    /// Rewards completed in earlier periods and not claimed yet
+    pub banked: u32,
+}
+
+impl QuestProgress {
+    /// Number of rewards the player can still claim
+    pub fn claimable(&self, target: u32) -> u32 {
+        self.banked + u32::from(!self.claimed && self.progress >= target)
+    }
+}
+
+/// Milestone a player can earn a badge for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    /// First ranked win
+    FirstWin,
+    /// Ten ranked wins in a row
+    WinStreak,
+    /// Ranked win without conceding a goal
+    Shutout,
+    /// One hundred games played
+    Centurion,
+    /// Won a tournament
+    TournamentChampion,
+}
+
+/// Badge for an achievement; bound to the player who earned it
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Badge {
+    pub achievement: Achievement,
+    /// Block timestamp when the badge was earned
+    pub earned_at: u64,
+    /// Game that completed the milestone, if a game did
+    pub game_id: Option<u64>,
+    /// Tournament that completed the milestone, if a tournament did
+    pub tournament_id: Option<u64>,
+}
+
+/// Badges a player has earned
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlayerBadges {
+    /// Badges in the order they were earned, at most one per achievement
+    pub badges: Vec<Badge>,
+    /// Ranked win streak as counted before streaks moved to `PlayerStats`;
+    /// no longer updated, only read to seed stats from before version 4
+    pub win_streak: u32,
+}
+
+impl PlayerBadges {
+    /// Check a player holds the badge for an achievement
+    pub fn has(&self, achievement: Achievement) -> bool {
+        self.badges
+            .iter()
+            .any(|badge| badge.achievement == achievement)
+    }
+
+    /// Award a badge unless the player already holds it
+    pub fn award(&mut self, badge: Badge) {
+        if !self.has(badge.achievement) {
+            self.badges.push(badge);
+        }
+    }
+}
+
+/// Status of a direct challenge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ChallengeStatus {
+    /// Waiting for the challenged player to answer
+    #[default]
+    Pending,
+    /// Accepted, game started
+    Accepted,
+    /// Declined by the challenged player
+    Declined,
+    /// Withdrawn by the challenger
+    Withdrawn,
+}
+
+/// A challenge issued on another chain, recorded on the challenged
+/// player's chain when its notification arrives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceivedChallenge {
+    /// Chain the challenge was issued on, where it is answered
+    pub origin_chain: ChainId,
+    /// The challenge as issued; its status is not updated here
+    pub challenge: Challenge,
+}
+
+/// A direct challenge from one player to another
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Challenge {
+    /// Unique challenge identifier
+    pub id: u64,
+    /// Address of the challenging player (player 1)
+    pub challenger: String,
+    /// Address of the challenged player (player 2)
+    pub opponent: String,
+    /// Stake amount per player
+    pub stake: Amount,
+    /// Token the stake is denominated in
+    pub currency: StakeCurrency,
+    /// Rules the game will be played under
+    pub rules: GameRules,
+    /// WebSocket room code for the game
+    pub room_code: String,
+    /// Current challenge status
+    pub status: ChallengeStatus,
+    /// Game started when the challenge was accepted
+    pub game_id: Option<u64>,
+    /// Block timestamp when challenge was issued
+    pub created_at: u64,
+    /// Block timestamp when challenge was answered or withdrawn
+    pub responded_at: Option<u64>,
+}
+
+/// Maximum length of an avatar reference (URL or content hash)
+pub const MAX_AVATAR_LENGTH: usize = 256;
+
+/// Public profile of a player
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlayerProfile {
+    /// Display name, unique ignoring case
+    pub nickname: String,
+    /// Avatar reference (URL or content hash)
+    pub avatar: Option<String>,
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    /// Block timestamp when the profile was first set
+    pub created_at: u64,
+}
+
+impl PlayerProfile {
+    /// Check a nickname is 3-20 letters, digits, `_` or `-`
+    pub fn is_valid_nickname(nickname: &str) -> bool {
+        (3..=20).contains(&nickname.len())
+            && nickname
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
+    /// Check a country is a two-letter uppercase code
+    pub fn is_valid_country(country: &str) -> bool {
+        country.len() == 2 && country.chars().all(|c| c.is_ascii_uppercase())
+    }
+
+    /// Check an avatar reference is non-empty and not too long
+    pub fn is_valid_avatar(avatar: &str) -> bool {
+        !avatar.is_empty() && avatar.len() <= MAX_AVATAR_LENGTH
+    }
+
+    /// Key used to keep nicknames unique regardless of case
+    pub fn nickname_key(nickname: &str) -> String {
+        nickname.to_ascii_lowercase()
+    }
+}
+
+/// A spectator's bet in a game's side pool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideBet {
+    /// Address of the bettor
+    pub bettor: String,
+    pub outcome: BetOutcome,
+    pub amount: Amount,
+    /// Amount returned to the bettor, None while the pool is open
+    pub payout: Option<Amount>,
+    /// Block timestamp when the bet was placed
+    pub placed_at: u64,
+}
+
+/// Parimutuel pool of spectator bets on a game, in the game's currency
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SidePool {
+    pub game_id: u64,
+    pub currency: StakeCurrency,
+    pub bets: Vec<SideBet>,
+    /// Whether the bets were returned because the game was voided
+    pub refunded: bool,
+    /// Block timestamp when the pool was paid out or refunded
+    pub closed_at: Option<u64>,
+}
+
+impl SidePool {
+    /// Create an empty pool for a game
+    pub fn new(game_id: u64, currency: StakeCurrency) -> Self {
+        Self {
+            game_id,
+            currency,
+            ..Self::default()
+        }
+    }
+
+    /// Check bets are still held in escrow
+    pub fn is_open(&self) -> bool {
+        self.closed_at.is_none()
+    }
+
+    /// Sum of all bets
+    pub fn total(&self) -> Amount {
+        self.bets.iter().map(|bet| &bet.amount).sum()
+    }
+
+    /// Sum of the bets on one outcome
+    pub fn total_on(&self, outcome: BetOutcome) -> Amount {
+        self.bets
+            .iter()
+            .filter(|bet| bet.outcome == outcome)
+            .map(|bet| &bet.amount)
+            .sum()
+    }
+
+    /// Split the whole pool between the bets on `outcome` in proportion to
+    /// their size, with the rounding remainder going to the first of them;
+    /// refund everyone if nobody backed it
+    pub fn settle(&mut self, outcome: BetOutcome, timestamp: u64) {
+        let winning = u128::from(self.total_on(outcome));
+        if winning == 0 {
+            self.refund(timestamp);
+            return;
+        }
+
+        let total = u128::from(self.total());
+        let mut paid = 0u128;
+        for bet in &mut self.bets {
+            let share = if bet.outcome == outcome {
+                let amount = u128::from(bet.amount);
+                match amount.checked_mul(total) {
+                    Some(product) => product / winning,
+                    // Only reachable with huge pools; lose some precision
+                    // rather than fail the settlement
+                    None => amount / winning * total + amount % winning * (total / winning),
+                }
+            } else {
+                0
+            };
+            paid += share;
+            bet.payout = Some(Amount::from_attos(share));
+        }
+
+        if let Some(first) = self.bets.iter_mut().find(|bet| bet.outcome == outcome) {
+            let share = u128::from(first.payout.unwrap_or_default());
+            first.payout = Some(Amount::from_attos(share + (total - paid)));
+        }
+        self.closed_at = Some(timestamp);
+    }
+
+    /// Return every bet in full
+    pub fn refund(&mut self, timestamp: u64) {
+        for bet in &mut self.bets {
+            bet.payout = Some(bet.amount);
+        }
+        self.refunded = true;
+        self.closed_at = Some(timestamp);
+    }
+}
+
+/// Sponsor-funded bonus for the winner of a game or tournament, kept apart
+/// from the prize the players' own stakes pay
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Sponsorship {
+    pub currency: StakeCurrency,
+    /// Amount each sponsor contributed, in order of first contribution
+    pub sponsors: Vec<(String, Amount)>,
+    /// Player the bonus was paid to
+    pub winner: Option<String>,
+    /// Whether the bonus went back to the sponsors
+    pub refunded: bool,
+    /// Block timestamp when the bonus was paid out or refunded
+    pub closed_at: Option<u64>,
+}
+
+impl Sponsorship {
+    /// Create an empty sponsorship
+    pub fn new(currency: StakeCurrency) -> Self {
+        Self {
+            currency,
+            ..Self::default()
+        }
+    }
+
+    /// Check the bonus is still held in escrow
+    pub fn is_open(&self) -> bool {
+        self.closed_at.is_none()
+    }
+
+    /// Sum of all contributions
+    pub fn total(&self) -> Amount {
+        self.sponsors.iter().map(|(_, amount)| amount).sum()
+    }
+
+    /// Add a contribution, merging repeat contributions from one sponsor
+    pub fn add(&mut self, sponsor: String, amount: Amount) {
+        match self
+            .sponsors
+            .iter_mut()
+            .find(|(existing, _)| *existing == sponsor)
+        {
+            Some((_, total)) => total.try_add_assign(amount).expect("Sponsorship overflow"),
+            None => self.sponsors.push((sponsor, amount)),
+        }
+    }
+
+    /// Pay the bonus to the winner, or refund the sponsors when there is none
+    pub fn close(&mut self, winner: Option<String>, timestamp: u64) {
+        self.refunded = winner.is_none();
+        self.winner = winner;
+        self.closed_at = Some(timestamp);
+    }
+}
+
+/// Difference between a recorded stake pool and the stakes actually in escrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolDrift {
+    pub currency: StakeCurrency,
+    /// Pool recorded in `AirHockeyState::stake_pools`
+    pub recorded: Amount,
+    /// Sum of escrowed stakes
+    pub expected: Amount,
+}
+
+/// Result of the last owner reconciliation
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Reconciliation {
+    /// Block timestamp of the reconciliation
+    pub reconciled_at: u64,
+    /// Pools that were corrected, with their values before the fix
+    pub drift: Vec<PoolDrift>,
+}
+
+/// A game record chained into a player's archive history hash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedGame {
+    /// History hash before this game
+    pub previous: Option<CryptoHash>,
+    pub game: Game,
+}
+
+impl BcsHashable<'_> for ArchivedGame {}
+
+/// Summary of a player's games whose full records were archived
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlayerArchive {
+    /// Completed games archived
+    pub games_completed: u64,
+    /// Archived games won
+    pub wins: u64,
+    /// Archived games lost
+    pub losses: u64,
+    /// Archived games drawn
+    pub draws: u64,
+    /// Cancelled or voided games archived
+    pub games_cancelled: u64,
+    /// Hash chaining every archived game record, when history hashing was requested
+    pub history_hash: Option<CryptoHash>,
+    /// ID of the most recently archived game
+    pub last_game_id: Option<u64>,
+}
+
+impl PlayerArchive {
+    /// Fold a finished game into the summary, optionally chaining its full record
+    pub fn record(&mut self, player: &str, game: &Game, hash_history: bool) {
+        match game.status {
+            GameStatus::Completed => {
+                self.games_completed += 1;
+                match game.winner.as_deref() {
+                    Some(winner) if winner == player => self.wins += 1,
+                    Some(_) => self.losses += 1,
+                    None => self.draws += 1,
+                }
+            }
+            _ => self.games_cancelled += 1,
+        }
+
+        if hash_history {
+            let entry = ArchivedGame {
+                previous: self.history_hash,
+                game: game.clone(),
+            };
+            self.history_hash = Some(CryptoHash::new(&entry));
+        }
+        self.last_game_id = Some(game.id);
+    }
+}
+
+/// Layout version of the stored state; bump it together with a new record
+/// variant or a register migration
+///
+/// Version 1 moved games and stats into versioned maps; entries written
+/// before it stay in the legacy maps, where reads fall back to, until
+/// `MigrateRecords` copies them across, and moved the native stake pool
+/// into `stake_pools`. Version 2 added the game status indexes and version 3 the disputed game
+/// index, both rebuilt by `MigrateRecords`. Version 4 added streaks to
+/// `PlayerStats` (`VersionedPlayerStats::V2`).
+pub const SCHEMA_VERSION: u32 = 4;
+
+/// A game as stored in `AirHockeyState::games`
+///
+/// When `Game` changes shape, freeze the old struct in a new variant and
+/// convert it in `From<VersionedGame>`; old entries upgrade when read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VersionedGame {
+    V1(Game),
+}
+
+impl VersionedGame {
+    /// Check the record is stored in the current layout
+    pub fn is_latest(&self) -> bool {
+        matches!(self, VersionedGame::V1(_))
+    }
+}
+
+impl From<Game> for VersionedGame {
+    fn from(game: Game) -> Self {
+        VersionedGame::V1(game)
+    }
+}
+
+impl From<VersionedGame> for Game {
+    fn from(record: VersionedGame) -> Self {
+        match record {
+            VersionedGame::V1(game) => game,
+        }
+    }
+}
+
+/// `Game` as first deployed, before stakes carried a currency; kept to
+/// read `AirHockeyState::legacy_games`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameV0 {
+    pub id: u64,
+    pub creator: String,
+    pub opponent: Option<String>,
+    /// Native stake per player in attos
+    pub stake: u64,
+    pub status: GameStatus,
+    pub winner: Option<String>,
+    pub created_at: u64,
+    pub started_at: Option<u64>,
+    pub ended_at: Option<u64>,
+    pub player1_score: u8,
+    pub player2_score: u8,
+    pub room_code: String,
+}
+
+impl From<GameV0> for Game {
+    fn from(game: GameV0) -> Self {
+        Self {
+            opponent: game.opponent,
+            status: game.status,
+            winner: game.winner,
+            started_at: game.started_at,
+            ended_at: game.ended_at,
+            player1_score: game.player1_score,
+            player2_score: game.player2_score,
+            ..Game::new(
+                game.id,
+                game.creator,
+                Amount::from_attos(game.stake.into()),
+                game.room_code,
+                game.created_at,
+            )
+        }
+    }
+}
+
+/// `PlayerStats` as first deployed, with native token totals in attos;
+/// kept to read `AirHockeyState::legacy_player_stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerStatsV0 {
+    pub games_played: u64,
+    pub wins: u64,
+    pub losses: u64,
     pub tokens_won: u64,
-    /// Total tokens lost
     pub tokens_lost: u64,
 }
 
+impl From<PlayerStatsV0> for PlayerStats {
+    fn from(stats: PlayerStatsV0) -> Self {
+        let mut tokens = Vec::new();
+        if stats.tokens_won > 0 || stats.tokens_lost > 0 {
+            tokens.push(TokenTotals {
+                currency: StakeCurrency::Native,
+                won: Amount::from_attos(stats.tokens_won.into()),
+                lost: Amount::from_attos(stats.tokens_lost.into()),
+            });
+        }
+        Self {
+            games_played: stats.games_played,
+            wins: stats.wins,
+            losses: stats.losses,
+            tokens,
+            ..Self::default()
+        }
+    }
+}
+
+/// `PlayerStats` before streak tracking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerStatsV1 {
+    /// Total games played
+    pub games_played: u64,
+    /// Games won
+    pub wins: u64,
+    /// Games lost
+    pub losses: u64,
+    /// Tokens won and lost, per currency
+    pub tokens: Vec<TokenTotals>,
+    /// Elo-style skill rating
+    pub rating: u32,
+    /// Games conceded or abandoned during a dispute
+    pub forfeits: u64,
+    /// Active games voided because no result arrived in time
+    pub expired_games: u64,
+    /// Tournaments cancelled while other players were registered
+    pub late_cancellations: u64,
+    /// Disputes opened and then lost on replay
+    pub lost_disputes: u64,
+}
+
+impl From<PlayerStatsV1> for PlayerStats {
+    fn from(stats: PlayerStatsV1) -> Self {
+        // Streaks start empty; past results were never recorded
+        Self {
+            games_played: stats.games_played,
+            wins: stats.wins,
+            losses: stats.losses,
+            tokens: stats.tokens,
+            rating: stats.rating,
+            forfeits: stats.forfeits,
+            expired_games: stats.expired_games,
+            late_cancellations: stats.late_cancellations,
+            lost_disputes: stats.lost_disputes,
+            ..Self::default()
+        }
+    }
+}
+
+/// Player statistics as stored in `AirHockeyState::player_stats` and
+/// `AirHockeyState::season_stats`, versioned like `VersionedGame`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VersionedPlayerStats {
+    V1(PlayerStatsV1),
+    V2(PlayerStats),
+}
+
+impl VersionedPlayerStats {
+    /// Check the record is stored in the current layout
+    pub fn is_latest(&self) -> bool {
+        matches!(self, VersionedPlayerStats::V2(_))
+    }
+}
+
+impl From<PlayerStats> for VersionedPlayerStats {
+    fn from(stats: PlayerStats) -> Self {
+        VersionedPlayerStats::V2(stats)
+    }
+}
+
+impl From<VersionedPlayerStats> for PlayerStats {
+    fn from(record: VersionedPlayerStats) -> Self {
+        match record {
+            VersionedPlayerStats::V1(stats) => stats.into(),
+            VersionedPlayerStats::V2(stats) => stats,
+        }
+    }
+}
+
 /// Application state stored on chain
+///
+/// Views are stored by field position, so new fields go at the end.
 #[derive(RootView)]
 #[view(context = ViewStorageContext)]
 pub struct AirHockeyState {
     /// Counter for game IDs
     pub next_game_id: RegisterView<u64>,
-    /// All games by ID
-    pub games: MapView<u64, Game>,
-    /// Player statistics by address
-    pub player_stats: MapView<String, PlayerStats>,
-    /// Total stake pool (all active games)
-    pub total_stake_pool: RegisterView<u64>,
+    /// Games stored before records were versioned, moved into `games` by
+    /// `MigrateRecords`
+    pub legacy_games: MapView<u64, GameV0>,
+    /// Player statistics stored before records were versioned, moved into
+    /// `player_stats` by `MigrateRecords`
+    pub legacy_player_stats: MapView<String, PlayerStatsV0>,
+    /// Native stake pool from before per-currency pools, moved into
+    /// `stake_pools` by the schema upgrade
+    pub legacy_total_stake_pool: RegisterView<u64>,
     /// Contract owner
     pub owner: RegisterView<String>,
+    /// Escrowed stakes of all active games, series, tournaments and
+    /// challenges, per currency
+    pub stake_pools: MapView<StakeCurrency, Amount>,
+    /// Counter for series IDs
+    pub next_series_id: RegisterView<u64>,
+    /// All best-of-N series by ID
+    pub series: MapView<u64, Series>,
+    /// Counter for tournament IDs
+    pub next_tournament_id: RegisterView<u64>,
+    /// All tournaments by ID
+    pub tournaments: MapView<u64, Tournament>,
+    /// Counter for season IDs
+    pub next_season_id: RegisterView<u64>,
+    /// Season currently open, if any
+    pub current_season: RegisterView<Option<u64>>,
+    /// All seasons by ID
+    pub seasons: MapView<u64, Season>,
+    /// Season statistics stored before records were versioned, moved into
+    /// `season_stats` by `MigrateRecords`
+    pub legacy_season_stats: MapView<(u64, String), PlayerStatsV1>,
+    /// Player profiles by address
+    pub player_profiles: MapView<String, PlayerProfile>,
+    /// Owner address of each taken nickname, keyed by lowercased nickname
+    pub nicknames: MapView<String, String>,
+    /// Friends list by player address
+    pub friends: MapView<String, Vec<String>>,
+    /// Players each player has blocked, by address
+    pub blocked_players: MapView<String, Vec<String>>,
+    /// Counter for challenge IDs
+    pub next_challenge_id: RegisterView<u64>,
+    /// All direct challenges by ID
+    pub challenges: MapView<u64, Challenge>,
+    /// IDs of pending challenges each player issued or received
+    pub pending_challenges: MapView<String, Vec<u64>>,
+    /// Revealed input logs of disputed games by game ID and player slot,
+    /// kept until both logs are in
+    pub revealed_inputs: MapView<(u64, u8), Vec<InputFrame>>,
+    /// Limits on repeated games between the same two players
+    pub collusion_limits: RegisterView<CollusionLimits>,
+    /// Games between each pair of players in the current window, keyed by
+    /// `pair_key`
+    pub pair_activity: MapView<(String, String), PairActivity>,
+    /// Block timestamp each pair was last flagged, for owner review
+    pub flagged_pairs: MapView<(String, String), u64>,
+    /// Lifetime record of each pair of players, keyed by `pair_key`
+    pub head_to_head: MapView<(String, String), HeadToHead>,
+    /// Layout version of the stored state, see `SCHEMA_VERSION`
+    pub schema_version: RegisterView<u32>,
+    /// Next game ID the record migration will check
+    pub migration_cursor: RegisterView<u64>,
+    /// All games by ID
+    pub games: MapView<u64, VersionedGame>,
+    /// Player statistics by address
+    pub player_stats: MapView<String, VersionedPlayerStats>,
+    /// Player statistics by season ID and address
+    pub season_stats: MapView<(u64, String), VersionedPlayerStats>,
+    /// IDs of games waiting for an opponent
+    pub waiting_games: SetView<u64>,
+    /// IDs of games in progress
+    pub active_games: SetView<u64>,
+    /// IDs of disputed games waiting for their replay
+    pub disputed_games: SetView<u64>,
+    /// IDs of the most recently completed games, oldest first
+    pub recent_completed_games: RegisterView<Vec<u64>>,
+    /// Oldest game ID that may still need archiving
+    pub archive_cursor: RegisterView<u64>,
+    /// Summaries of archived games by player address
+    pub player_archives: MapView<String, PlayerArchive>,
+    /// Spectator side pool of each game that received bets
+    pub side_pools: MapView<u64, SidePool>,
+    /// Next quest ID
+    pub next_quest_id: RegisterView<u64>,
+    /// All quests ever defined
+    pub quests: MapView<u64, Quest>,
+    /// Quests that settled games count toward
+    pub active_quests: RegisterView<Vec<u64>>,
+    /// Progress keyed by (quest ID, player)
+    pub quest_progress: MapView<(u64, String), QuestProgress>,
+    /// Achievement badges of each player
+    pub player_badges: MapView<String, PlayerBadges>,
+    /// Season and quest rewards each player received
+    pub reward_totals: MapView<String, RewardTotals>,
+    /// Sponsor bonus of each sponsored game
+    pub game_sponsorships: MapView<u64, Sponsorship>,
+    /// Sponsor bonus of each sponsored tournament
+    pub tournament_sponsorships: MapView<u64, Sponsorship>,
+    /// Result of the last owner reconciliation of the stake pools
+    pub last_reconciliation: RegisterView<Option<Reconciliation>>,
+    /// Results waiting for confirmation, by game ID
+    pub pending_results: MapView<u64, PendingResult>,
+    /// Next game ID the archival step will check; wraps back to
+    /// `archive_cursor` after the newest game
+    pub archive_scan_cursor: RegisterView<u64>,
+    /// Optional goal-by-goal timelines by game ID
+    pub goal_timelines: MapView<u64, Vec<GoalEvent>>,
+    /// Challenges issued on other chains, by challenged player address
+    pub received_challenges: MapView<String, Vec<ReceivedChallenge>>,
+}
+
+impl AirHockeyState {
+    /// Get a game, falling back to the legacy map for unmigrated games
+    pub async fn load_game(&self, id: u64) -> Option<Game> {
+        match self.games.get(&id).await.expect("Failed to get game") {
+            Some(record) => Some(record.into()),
+            None => self
+                .legacy_games
+                .get(&id)
+                .await
+                .expect("Failed to get game")
+                .map(Game::from),
+        }
+    }
+
+    /// Get a player's lifetime stats, falling back to the legacy map for
+    /// unmigrated players
+    pub async fn load_player_stats(&self, player: &str) -> Option<PlayerStats> {
+        let player = player.to_string();
+        let mut stats = match self
+            .player_stats
+            .get(&player)
+            .await
+            .expect("Failed to get stats")
+        {
+            Some(VersionedPlayerStats::V2(stats)) => return Some(stats),
+            Some(VersionedPlayerStats::V1(stats)) => PlayerStats::from(stats),
+            None => PlayerStats::from(
+                self.legacy_player_stats
+                    .get(&player)
+                    .await
+                    .expect("Failed to get stats")?,
+            ),
+        };
+
+        // Stats from before streak tracking pick up the streak the badges
+        // counted; the badges only kept the current run, so the longest
+        // streak is a lower bound that later wins raise through `max`
+        let badges = self
+            .player_badges
+            .get(&player)
+            .await
+            .expect("Failed to get badges");
+        if let Some(badges) = badges {
+            stats.current_streak = i32::try_from(badges.win_streak).unwrap_or(i32::MAX);
+            stats.longest_win_streak = stats.longest_win_streak.max(badges.win_streak);
+        }
+        Some(stats)
+    }
+
+    /// Get a player's stats for a season, keyed by (season ID, player),
+    /// falling back to the legacy map for unmigrated players
+    pub async fn load_season_stats(&self, key: &(u64, String)) -> Option<PlayerStats> {
+        match self
+            .season_stats
+            .get(key)
+            .await
+            .expect("Failed to get stats")
+        {
+            Some(record) => Some(record.into()),
+            None => self
+                .legacy_season_stats
+                .get(key)
+                .await
+                .expect("Failed to get stats")
+                .map(PlayerStats::from),
+        }
+    }
+
+    /// Sum the stakes held in escrow by waiting and in-play games and their
+    /// side pools, by series and tournaments, by pending challenges, by open
+    /// sponsorships and by the reward pools of the open season and of every
+    /// quest, per currency
+    ///
+    /// Walks every series, tournament, challenge and quest, so it is meant
+    /// for owner checks rather than regular operations.
+    pub async fn escrowed_stakes(&self) -> BTreeMap<StakeCurrency, Amount> {
+        let mut escrow: BTreeMap<StakeCurrency, Amount> = BTreeMap::new();
+        let mut add = |currency: StakeCurrency, amount: Amount| {
+            escrow
+                .entry(currency)
+                .or_default()
+                .try_add_assign(amount)
+                .expect("Escrow overflow");
+        };
+
+        let waiting = self
+            .waiting_games
+            .indices()
+            .await
+            .expect("Failed to get waiting games");
+        let mut in_play = self
+            .active_games
+            .indices()
+            .await
+            .expect("Failed to get active games");
+        in_play.extend(
+            self.disputed_games
+                .indices()
+                .await
+                .expect("Failed to get disputed games"),
+        );
+        for (ids, both_players) in [(waiting, false), (in_play, true)] {
+            for id in ids {
+                let Some(game) = self.load_game(id).await else {
+                    continue;
+                };
+                add(
+                    game.currency,
+                    if both_players {
+                        game.total_pot()
+                    } else {
+                        game.stake
+                    },
+                );
+
+                let pool = self
+                    .side_pools
+                    .get(&id)
+                    .await
+                    .expect("Failed to get side pool");
+                if let Some(pool) = pool.filter(SidePool::is_open) {
+                    add(pool.currency, pool.total());
+                }
+            }
+        }
+
+        self.series
+            .for_each_index_value(|_, series| {
+                match series.status {
+                    GameStatus::Waiting => add(series.currency, series.stake),
+                    GameStatus::Active => add(series.currency, series.total_pot()),
+                    _ => {}
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to scan series");
+
+        self.tournaments
+            .for_each_index_value(|_, tournament| {
+                if matches!(tournament.status, GameStatus::Waiting | GameStatus::Active) {
+                    add(tournament.currency, tournament.prize_pool());
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to scan tournaments");
+
+        self.challenges
+            .for_each_index_value(|_, challenge| {
+                if challenge.status == ChallengeStatus::Pending {
+                    add(challenge.currency, challenge.stake);
+                }
+                Ok(())
+            })
+            .await
+            .expect("Failed to scan challenges");
+
+        if let Some(season_id) = *self.current_season.get() {
+            let season = self
+                .seasons
+                .get(&season_id)
+                .await
+                .expect("Failed to get season");
+            if let Some(season) = season {
+                add(StakeCurrency::Native, season.reward_pool);
+            }
+        }
+
+        self.quests
+            .for_each_index_value(|_, quest| {
+                add(StakeCurrency::Native, quest.reward_pool);
+                Ok(())
+            })
+            .await
+            .expect("Failed to scan quests");
+
+        for sponsorships in [&self.game_sponsorships, &self.tournament_sponsorships] {
+            sponsorships
+                .for_each_index_value(|_, sponsorship| {
+                    if sponsorship.is_open() {
+                        add(sponsorship.currency, sponsorship.total());
+                    }
+                    Ok(())
+                })
+                .await
+                .expect("Failed to scan sponsorships");
+        }
+
+        escrow
+    }
+
+    /// Compare the recorded stake pools with the stakes in escrow, returning
+    /// every currency where they differ
+    pub async fn stake_pool_drift(&self) -> Vec<PoolDrift> {
+        let mut expected = self.escrowed_stakes().await;
+        let mut drift = Vec::new();
+
+        let recorded = self
+            .stake_pools
+            .index_values()
+            .await
+            .expect("Failed to get stake pools");
+        for (currency, recorded) in recorded {
+            let expected = expected.remove(&currency).unwrap_or_default();
+            if recorded != expected {
+                drift.push(PoolDrift {
+                    currency,
+                    recorded,
+                    expected,
+                });
+            }
+        }
+        for (currency, expected) in expected {
+            if !expected.is_zero() {
+                drift.push(PoolDrift {
+                    currency,
+                    recorded: Amount::ZERO,
+                    expected,
+                });
+            }
+        }
+
+        drift
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 1_000_000_000;
+    const SECOND: u64 = 1_000_000;
+
+    fn limits() -> CollusionLimits {
+        CollusionLimits {
+            max_games: 3,
+            max_net_tokens: Amount::from_tokens(10),
+            window_secs: 60,
+        }
+    }
+
+    #[test]
+    fn baseline_records_decode() {
+        // Field by field as the first deployment encoded them
+        let game = linera_sdk::bcs::to_bytes(&(
+            7u64,
+            "alice".to_string(),
+            Some("bob".to_string()),
+            250u64,
+            GameStatus::Completed,
+            Some("bob".to_string()),
+            100u64,
+            Some(200u64),
+            Some(300u64),
+            3u8,
+            7u8,
+            "ROOM".to_string(),
+        ))
+        .unwrap();
+        let game = Game::from(linera_sdk::bcs::from_bytes::<GameV0>(&game).unwrap());
+        assert_eq!(game.id, 7);
+        assert_eq!(game.opponent.as_deref(), Some("bob"));
+        assert_eq!(game.stake, Amount::from_attos(250));
+        assert_eq!(game.currency, StakeCurrency::Native);
+        assert_eq!(game.status, GameStatus::Completed);
+        assert_eq!(game.winner.as_deref(), Some("bob"));
+        assert_eq!((game.created_at, game.ended_at), (100, Some(300)));
+        assert_eq!((game.player1_score, game.player2_score), (3, 7));
+        assert_eq!(game.room_code, "ROOM");
+
+        let stats = linera_sdk::bcs::to_bytes(&(5u64, 3u64, 2u64, 900u64, 400u64)).unwrap();
+        let stats =
+            PlayerStats::from(linera_sdk::bcs::from_bytes::<PlayerStatsV0>(&stats).unwrap());
+        assert_eq!((stats.games_played, stats.wins, stats.losses), (5, 3, 2));
+        assert_eq!(
+            stats.tokens,
+            vec![TokenTotals {
+                currency: StakeCurrency::Native,
+                won: Amount::from_attos(900),
+                lost: Amount::from_attos(400),
+            }]
+        );
+        assert_eq!(stats.rating, INITIAL_RATING);
+    }
+
+    #[test]
+    fn seeded_longest_streak_only_grows() {
+        let mut stats = PlayerStats {
+            current_streak: 3,
+            longest_win_streak: 3,
+            ..PlayerStats::default()
+        };
+        stats.record_result(GameResult::Win);
+        assert_eq!((stats.current_streak, stats.longest_win_streak), (4, 4));
+
+        stats.record_result(GameResult::Loss);
+        stats.record_result(GameResult::Win);
+        stats.record_result(GameResult::Win);
+        assert_eq!((stats.current_streak, stats.longest_win_streak), (2, 4));
+    }
+
+    fn side_pool(bets: &[(BetOutcome, u128)]) -> SidePool {
+        let mut pool = SidePool::new(1, StakeCurrency::Native);
+        for (index, (outcome, amount)) in bets.iter().enumerate() {
+            pool.bets.push(SideBet {
+                bettor: format!("bettor{index}"),
+                outcome: *outcome,
+                amount: Amount::from_attos(*amount),
+                payout: None,
+                placed_at: START,
+            });
+        }
+        pool
+    }
+
+    fn payouts(pool: &SidePool) -> Vec<u128> {
+        pool.bets
+            .iter()
+            .map(|bet| u128::from(bet.payout.expect("Bet not settled")))
+            .collect()
+    }
+
+    #[test]
+    fn side_pool_pays_winners_in_proportion() {
+        let mut pool = side_pool(&[
+            (BetOutcome::Player1, 100),
+            (BetOutcome::Player2, 300),
+            (BetOutcome::Player1, 300),
+            (BetOutcome::Draw, 200),
+        ]);
+        pool.settle(BetOutcome::Player1, START);
+
+        assert_eq!(payouts(&pool), vec![225, 0, 675, 0]);
+        assert_eq!(
+            payouts(&pool).iter().sum::<u128>(),
+            u128::from(pool.total())
+        );
+        assert!(!pool.refunded);
+        assert_eq!(pool.closed_at, Some(START));
+    }
+
+    #[test]
+    fn side_pool_refunds_when_nobody_backed_the_outcome() {
+        let mut pool = side_pool(&[(BetOutcome::Player1, 100), (BetOutcome::Player2, 300)]);
+        pool.settle(BetOutcome::Draw, START);
+
+        assert_eq!(payouts(&pool), vec![100, 300]);
+        assert!(pool.refunded);
+        assert_eq!(pool.closed_at, Some(START));
+    }
+
+    #[test]
+    fn side_pool_rounding_remainder_goes_to_the_first_winner() {
+        let mut pool = side_pool(&[
+            (BetOutcome::Player2, 1),
+            (BetOutcome::Player1, 1),
+            (BetOutcome::Player2, 1),
+            (BetOutcome::Player2, 1),
+            (BetOutcome::Draw, 3),
+        ]);
+        pool.settle(BetOutcome::Player2, START);
+
+        assert_eq!(payouts(&pool), vec![3, 0, 2, 2, 0]);
+        assert_eq!(
+            payouts(&pool).iter().sum::<u128>(),
+            u128::from(pool.total())
+        );
+    }
+
+    #[test]
+    fn pair_is_flagged_only_past_the_game_limit() {
+        let mut activity = PairActivity::default();
+        for game in 0..3 {
+            assert!(activity.record(Some(0), Amount::ZERO, START + game * SECOND, &limits()));
+        }
+        assert_eq!(activity.games, 3);
+        assert_eq!(activity.flagged_at, None);
+
+        assert!(!activity.record(Some(0), Amount::ZERO, START + 3 * SECOND, &limits()));
+        assert_eq!(activity.flagged_at, Some(START + 3 * SECOND));
+        assert!(activity.is_flagged(START + 4 * SECOND, &limits()));
+    }
+
+    #[test]
+    fn pair_is_flagged_only_past_the_net_token_limit() {
+        let mut activity = PairActivity::default();
+        assert!(activity.record(Some(0), Amount::from_tokens(6), START, &limits()));
+        assert!(activity.record_tokens(Some(0), Amount::from_tokens(4), START + SECOND, &limits()));
+        assert_eq!(activity.tokens_won[0], Amount::from_tokens(10));
+
+        // Winnings the other way net out
+        assert!(activity.record(
+            Some(1),
+            Amount::from_tokens(5),
+            START + 2 * SECOND,
+            &limits()
+        ));
+        assert!(activity.record_tokens(
+            Some(0),
+            Amount::from_tokens(5),
+            START + 3 * SECOND,
+            &limits()
+        ));
+        assert!(!activity.record_tokens(
+            Some(0),
+            Amount::from_attos(1),
+            START + 4 * SECOND,
+            &limits()
+        ));
+    }
+
+    #[test]
+    fn window_expiry_resets_the_counts() {
+        let mut activity = PairActivity::default();
+        for game in 0..4 {
+            activity.record(
+                Some(0),
+                Amount::from_tokens(1),
+                START + game * SECOND,
+                &limits(),
+            );
+        }
+        assert!(activity.is_flagged(START + 59 * SECOND, &limits()));
+        assert!(!activity.is_flagged(START + 60 * SECOND, &limits()));
+
+        assert!(activity.record(Some(1), Amount::ZERO, START + 60 * SECOND, &limits()));
+        assert_eq!(activity.window_started_at, START + 60 * SECOND);
+        assert_eq!(activity.games, 1);
+        assert_eq!(activity.tokens_won, [Amount::ZERO; 2]);
+    }
 }